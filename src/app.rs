@@ -1,68 +1,76 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::env;
+use std::ffi::OsString;
+use std::iter::Skip;
+use std::path::Path;
 
 use atty::{self, Stream};
 
-use clap::{App as ClapApp, AppSettings, Arg, ArgGroup, ArgMatches, SubCommand};
+use clap::{App as ClapApp, AppSettings, Arg, ArgGroup, ArgMatches, SubCommand, Values};
 
 use console::Term;
 
-#[cfg(windows)]
-use ansi_term;
-
-use assets::BAT_THEME_DEFAULT;
-use errors::*;
-use line_range::LineRange;
-use style::{OutputComponent, OutputComponents, OutputWrap};
-
-#[derive(Debug, Clone, Copy)]
-pub enum PagingMode {
-    Always,
-    QuitIfOneScreen,
-    Never,
-}
+use encoding_rs::Encoding;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum InputFile<'a> {
-    StdIn,
-    Ordinary(&'a str),
-    ThemePreviewFile,
-}
-
-#[derive(Clone)]
-pub struct Config<'a> {
-    /// List of files to print
-    pub files: Vec<InputFile<'a>>,
+use regex::Regex;
 
-    /// The explicitly configured language, if any
-    pub language: Option<&'a str>,
+#[cfg(windows)]
+use ansi_term;
 
-    /// The character width of the terminal
-    pub term_width: usize,
+use bat::assets::BAT_THEME_DEFAULT;
+use bat::config::{Config, PagingMode};
+use bat::errors::*;
+use bat::inputfile::InputFile;
+use bat::line_range::{LineRange, LineRanges};
+use bat::project_config::ProjectConfig;
+use bat::style::{OutputComponent, OutputComponents, OutputWrap};
+use bat::syntax_mapping::SyntaxMapping;
+use bat::walk::FileFilter;
 
-    /// Whether or not to simply loop through all input (`cat` mode)
-    pub loop_through: bool,
+use config_file::get_args_from_config_file;
 
-    /// Whether or not the output should be colorized
-    pub colored_output: bool,
+/// Options which can be given multiple times. If they are given on the command line, the
+/// values from the configuration file are replaced instead of being combined with them.
+const REPEATABLE_OPTIONS: &[&str] = &[
+    "map-syntax",
+    "file-name",
+    "line-range",
+    "highlight-line",
+    "glob",
+    "exclude",
+];
 
-    /// Whether or not the output terminal supports true color
-    pub true_color: bool,
+const STYLE_COMPONENTS: &[&str] = &[
+    "auto", "full", "plain", "changes", "header", "grid", "numbers", "blame",
+];
 
-    /// Style elements (grid, line numbers, ...)
-    pub output_components: OutputComponents,
+fn validate_style(value: &str) -> ::std::result::Result<(), String> {
+    match value.split(',').find(|style| !STYLE_COMPONENTS.contains(style)) {
+        Some(style) => Err(format!("Unknown style component '{}'", style)),
+        None => Ok(()),
+    }
+}
 
-    /// Text wrapping mode
-    pub output_wrap: OutputWrap,
+/// Split an input of the form 'REV:path' (like 'HEAD~2:src/main.rs') into the Git revision and
+/// the path. Existing files are never split, even if their name contains a colon, and neither
+/// are paths starting with a drive letter (like 'C:\src\main.rs').
+fn split_revision(input: &str) -> Option<(&str, &str)> {
+    let pos = input.find(':')?;
+    if pos == 0 || pos + 1 == input.len() || Path::new(input).exists() {
+        return None;
+    }
 
-    /// Pager or STDOUT
-    pub paging_mode: PagingMode,
+    let is_drive_letter = pos == 1 && input.as_bytes()[0].is_ascii_alphabetic();
+    if is_drive_letter {
+        return None;
+    }
 
-    /// The range lines that should be printed, if specified
-    pub line_range: Option<LineRange>,
+    Some((&input[..pos], &input[pos + 1..]))
+}
 
-    /// The syntax highlighting theme
-    pub theme: String,
+fn print_warning(message: &str) {
+    use ansi_term::Colour::Yellow;
+    eprintln!("{}: {}", Yellow.paint("[bat warning]"), message);
 }
 
 fn is_truecolor_terminal() -> bool {
@@ -71,15 +79,13 @@ fn is_truecolor_terminal() -> bool {
         .unwrap_or(false)
 }
 
-/// Helper function that should might appear in Rust stable at some point
-/// (https://doc.rust-lang.org/stable/std/option/enum.Option.html#method.transpose)
-fn transpose<T>(opt: Option<Result<T>>) -> Result<Option<T>> {
-    opt.map_or(Ok(None), |res| res.map(Some))
-}
-
 pub struct App {
     pub matches: ArgMatches<'static>,
     interactive_output: bool,
+
+    /// The number of values from the configuration file to skip for options which can be given
+    /// multiple times, see `App::values_of`
+    skipped_values: HashMap<&'static str, usize>,
 }
 
 impl App {
@@ -89,13 +95,71 @@ impl App {
         #[cfg(windows)]
         let interactive_output = interactive_output && ansi_term::enable_ansi_support().is_ok();
 
+        let (matches, skipped_values) = Self::matches(interactive_output);
+
         App {
-            matches: Self::matches(interactive_output),
+            matches,
             interactive_output,
+            skipped_values,
+        }
+    }
+
+    fn matches(interactive_output: bool) -> (ArgMatches<'static>, HashMap<&'static str, usize>) {
+        let mut args: Vec<OsString> = env::args_os().collect();
+
+        // The default arguments from the configuration file go first, such that the arguments
+        // on the command line override them. They are not passed to subcommands. A broken
+        // configuration file should not make bat unusable (including '--help'), so it is only
+        // reported.
+        let mut config_matches = None;
+        if args.get(1).map_or(true, |arg| arg != "cache") {
+            let config_args = get_args_from_config_file().unwrap_or_else(|error| {
+                print_warning(&error.to_string());
+                vec![]
+            });
+
+            // The arguments are checked on their own first, an error would otherwise be reported
+            // as if it was on the command line. The error is not colored, as it is only quoted.
+            if !config_args.is_empty() {
+                let program = env::args_os().take(1);
+                match Self::clap_app(false)
+                    .get_matches_from_safe(program.chain(config_args.iter().cloned()))
+                {
+                    Ok(matches) => {
+                        config_matches = Some(matches);
+                        args.splice(1..1, config_args);
+                    }
+                    Err(error) => {
+                        let message = error.message.lines().next().unwrap_or("");
+                        print_warning(&format!(
+                            "Ignoring the arguments from the configuration file: {}",
+                            message.replacen("error: ", "", 1)
+                        ))
+                    }
+                }
+            }
         }
+
+        let matches = Self::clap_app(interactive_output).get_matches_from(args);
+
+        // Options which can be given multiple times are combined by clap. If such an option is
+        // given on the command line, its values from the configuration file are skipped (they
+        // come first).
+        let mut skipped_values = HashMap::new();
+        if let Some(config_matches) = config_matches {
+            for &option in REPEATABLE_OPTIONS {
+                let from_config = config_matches.values_of(option).map_or(0, Iterator::count);
+                let total = matches.values_of(option).map_or(0, Iterator::count);
+                if from_config > 0 && total > from_config {
+                    skipped_values.insert(option, from_config);
+                }
+            }
+        }
+
+        (matches, skipped_values)
     }
 
-    fn matches(interactive_output: bool) -> ArgMatches<'static> {
+    fn clap_app(interactive_output: bool) -> ClapApp<'static, 'static> {
         let clap_color_setting = if interactive_output {
             AppSettings::ColoredHelp
         } else {
@@ -122,7 +186,8 @@ impl App {
                     .help("File(s) to print / concatenate. Use '-' for standard input.")
                     .long_help(
                         "File(s) to print / concatenate. Use a dash ('-') or no argument at all \
-                         to read from standard input.",
+                         to read from standard input. A file from a Git revision can be given as \
+                         'REV:path' (like 'HEAD~2:src/main.rs'), see '--rev'.",
                     ).multiple(true)
                     .empty_values(false),
                     )
@@ -138,6 +203,60 @@ impl App {
                         (like 'cpp', 'hpp' or 'md'). Use '--list-languages' to show all supported \
                         language names and file extensions."
                     ).takes_value(true),
+            ).arg(
+                Arg::with_name("map-syntax")
+                    .long("map-syntax")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .multiple(true)
+                    .value_name("glob:syntax")
+                    .help("Use the specified syntax for files matching the glob pattern.")
+                    .long_help(
+                        "Map a glob pattern to an existing syntax name. The pattern is matched \
+                         against the full path as well as against the file name, before the \
+                         built-in syntax detection. Example: '--map-syntax \"*.conf:INI\"'. \
+                         If several patterns match, the last one given takes precedence.",
+                    ),
+            ).arg(
+                Arg::with_name("file-name")
+                    .long("file-name")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .multiple(true)
+                    .value_name("name")
+                    .help("Specify the name to display for a file.")
+                    .long_help(
+                        "Specify the name to display for a file in the header, instead of the \
+                         actual path (or 'STDIN'). The name is also used to detect the syntax, \
+                         e.g. 'git show HEAD:src/main.rs | bat --file-name main.rs'. If given \
+                         multiple times, the names are matched to the files by position.",
+                    ),
+            ).arg(
+                Arg::with_name("rev")
+                    .long("rev")
+                    .overrides_with("rev")
+                    .takes_value(true)
+                    .value_name("rev")
+                    .help("Print the files as they are in the given Git revision.")
+                    .long_help(
+                        "Print the files as they are in the given Git revision (like 'v0.5.0' \
+                         or 'HEAD~2'). The contents are read straight from the repository. \
+                         Paths are relative to the current directory. A single file can also \
+                         be given as 'REV:path'.",
+                    ),
+            ).arg(
+                Arg::with_name("encoding")
+                    .long("encoding")
+                    .overrides_with("encoding")
+                    .takes_value(true)
+                    .value_name("encoding")
+                    .help("Set the encoding of the input files.")
+                    .long_help(
+                        "Set the encoding of input files which do not start with a byte order \
+                         mark (like 'latin1', 'windows-1252' or 'shift_jis'). UTF-16 files with \
+                         a byte order mark are always detected automatically. By default, input \
+                         is assumed to be UTF-8. Binary input is never decoded.",
+                    ),
             ).arg(
                 Arg::with_name("list-languages")
                     .long("list-languages")
@@ -164,27 +283,32 @@ impl App {
             ).arg(
                 Arg::with_name("style")
                     .long("style")
+                    .overrides_with("style")
                     .value_name("style-components")
-                    .use_delimiter(true)
+                    // Clap's delimiter is turned off, as it does not work together with
+                    // 'overrides_with'. The components are validated and split manually.
+                    .use_delimiter(false)
                     .takes_value(true)
-                    .possible_values(&[
-                        "auto", "full", "plain", "changes", "header", "grid", "numbers",
-                    ]).default_value("auto")
+                    .validator(|value| validate_style(&value))
+                    .default_value("auto")
                     .help("Comma-separated list of style elements to display.")
                     .long_help(
                         "Configure which elements (line numbers, file headers, grid \
                          borders, Git modifications, ..) to display in addition to the \
                          file contents. The argument is a comma-separated list of \
                          components to display (e.g. 'numbers,changes,grid') or a \
-                         pre-defined style ('full')",
+                         pre-defined style ('full'). The 'blame' component shows the commit, \
+                         the author's initials and the age of every line, it is not part of \
+                         'full' [possible values: auto, full, plain, changes, header, grid, \
+                         numbers, blame]",
                     ),
             ).arg(
                 Arg::with_name("plain")
                     .overrides_with("plain")
                     .short("p")
                     .long("plain")
-                    .conflicts_with("style")
-                    .conflicts_with("number")
+                    .overrides_with("style")
+                    .overrides_with("number")
                     .help("Show plain style (alias for '--style=plain').")
                     .long_help(
                         "Only show plain style, no decorations. This is an alias for \
@@ -195,17 +319,106 @@ impl App {
                     .long("number")
                     .overrides_with("number")
                     .short("n")
-                    .conflicts_with("style")
+                    .overrides_with("style")
                     .help("Show line numbers (alias for '--style=numbers').")
                     .long_help(
                         "Only show line numbers, no other decorations. This is an alias for \
                          '--style=numbers'",
                     ),
+            ).arg(
+                Arg::with_name("diff-base")
+                    .long("diff-base")
+                    .overrides_with("diff-base")
+                    .takes_value(true)
+                    .value_name("rev")
+                    .help("Show Git modifications compared to the given revision.")
+                    .long_help(
+                        "Show the Git modifications (see '--style=changes') compared to the \
+                         given revision (like 'origin/master' or 'HEAD~3') instead of 'HEAD', \
+                         such that they include all changes of a branch. Changes which are not \
+                         staged yet are still marked separately.",
+                    ),
+            ).arg(
+                Arg::with_name("diff")
+                    .long("diff")
+                    .overrides_with("diff")
+                    .help("Only print the lines that have been modified.")
+                    .long_help(
+                        "Only print the lines that have been added, removed or modified in Git \
+                         (and their context, see '--diff-context'). Files without modifications \
+                         are skipped, as well as standard input and files outside of a Git \
+                         repository.",
+                    ),
+            ).arg(
+                Arg::with_name("diff-context")
+                    .long("diff-context")
+                    .overrides_with("diff-context")
+                    .takes_value(true)
+                    .value_name("N")
+                    .requires("diff")
+                    .validator(|c| {
+                        c.parse::<usize>()
+                            .map(|_| ())
+                            .map_err(|_| "must be a non-negative number".to_owned())
+                    }).help("Print N lines of context around modified lines.")
+                    .long_help(
+                        "Print N lines before and after every modified line. Only used with \
+                         '--diff' [default: 3]",
+                    ),
+            ).arg(
+                Arg::with_name("inline-diff")
+                    .long("inline-diff")
+                    .overrides_with("inline-diff")
+                    .help("Show removed lines in between the current ones.")
+                    .long_help(
+                        "Show the lines that have been removed in Git (compared to 'HEAD' or to \
+                         the revision given with '--diff-base') at the position where they were \
+                         removed, highlighted with a red background. Modified lines are shown \
+                         as pairs of the old and the new lines.",
+                    ),
+            ).arg(
+                Arg::with_name("blame-heatmap")
+                    .long("blame-heatmap")
+                    .overrides_with("blame-heatmap")
+                    .help("Color the blame of every line by its age.")
+                    .long_help(
+                        "Color the blame of every line (see '--style=blame') by the age of \
+                         the commit, from recent (red) to old (blue) commits of the file.",
+                    ),
+            ).arg(
+                Arg::with_name("tabs")
+                    .long("tabs")
+                    .overrides_with("tabs")
+                    .takes_value(true)
+                    .value_name("T")
+                    .validator(|t| {
+                        t.parse::<usize>()
+                            .map(|_| ())
+                            .map_err(|_| "must be a non-negative number".to_owned())
+                    }).help("Set the tab width to T spaces.")
+                    .long_help(
+                        "Set the tab width to T spaces. Tabs are expanded to the next tab stop, \
+                         relative to the start of the line contents (not the decorations). Use \
+                         a width of 0 to pass tabs through to the terminal [default: 4]",
+                    ),
+            ).arg(
+                Arg::with_name("show-all")
+                    .long("show-all")
+                    .overrides_with("show-all")
+                    .short("A")
+                    .help("Show non-printable characters (space, tab, newline, ..).")
+                    .long_help(
+                        "Show non-printable characters like space, tab or newline as visible \
+                         glyphs. Control characters and non-ASCII whitespace (like \
+                         non-breaking or zero-width spaces) are shown as well.",
+                    ),
             ).arg(
                 Arg::with_name("line-range")
                     .long("line-range")
-                    .overrides_with("line-range")
+                    .multiple(true)
+                    .number_of_values(1)
                     .takes_value(true)
+                    .allow_hyphen_values(true)
                     .value_name("N:M")
                     .help("Only print the lines from N to M.")
                     .long_help(
@@ -213,7 +426,111 @@ impl App {
                          For example:\n  \
                          '--line-range 30:40' prints lines 30 to 40\n  \
                          '--line-range :40' prints lines 1 to 40\n  \
-                         '--line-range 40:' prints lines 40 to the end of the file",
+                         '--line-range 40:' prints lines 40 to the end of the file\n  \
+                         '--line-range 40' only prints line 40\n  \
+                         '--line-range 40:+10' prints lines 40 to 50\n  \
+                         '--line-range 40::3' prints lines 37 to 43\n  \
+                         '--line-range -20:' prints the last 20 lines\n\
+                         The option can be given multiple times to print several ranges. \
+                         Overlapping ranges are merged, other ranges are separated by a line \
+                         ('--' without the grid).",
+                    ),
+            ).arg(
+                Arg::with_name("highlight-line")
+                    .long("highlight-line")
+                    .short("H")
+                    .multiple(true)
+                    .number_of_values(1)
+                    .takes_value(true)
+                    .allow_hyphen_values(true)
+                    .value_name("N:M")
+                    .help("Highlight lines N through M.")
+                    .long_help(
+                        "Highlight the specified line ranges with a different background \
+                         color. The ranges take the same forms as in '--line-range', for \
+                         example:\n  \
+                         '--highlight-line 40' highlights line 40\n  \
+                         '--highlight-line 30:40' highlights lines 30 to 40\n\
+                         The option can be given multiple times.",
+                    ),
+            ).arg(
+                Arg::with_name("pattern")
+                    .long("pattern")
+                    .overrides_with("pattern")
+                    .takes_value(true)
+                    .value_name("regex")
+                    .help("Highlight all matches of the regular expression.")
+                    .long_help(
+                        "Highlight all matches of the given regular expression on top of the \
+                         syntax highlighting. Use '--only-matching-lines' to hide all lines \
+                         without a match.",
+                    ),
+            ).arg(
+                Arg::with_name("only-matching-lines")
+                    .long("only-matching-lines")
+                    .overrides_with("only-matching-lines")
+                    .requires("pattern")
+                    .help("Only print the lines that match the pattern.")
+                    .long_help(
+                        "Only print the lines that match the pattern given with '--pattern' \
+                         (and their context, see '--context'). Lines keep their original line \
+                         numbers, and groups of lines are separated like with '--line-range'.",
+                    ),
+            ).arg(
+                Arg::with_name("context")
+                    .long("context")
+                    .short("C")
+                    .overrides_with("context")
+                    .takes_value(true)
+                    .value_name("N")
+                    .requires("only-matching-lines")
+                    .validator(|c| {
+                        c.parse::<usize>()
+                            .map(|_| ())
+                            .map_err(|_| "must be a non-negative number".to_owned())
+                    }).help("Print N lines of context around matching lines.")
+                    .long_help(
+                        "Print N lines before and after every line that matches the pattern. \
+                         Only used with '--only-matching-lines'.",
+                    ),
+            ).arg(
+                Arg::with_name("recursive")
+                    .long("recursive")
+                    .overrides_with("recursive")
+                    .short("r")
+                    .help("Print all files inside of directories.")
+                    .long_help(
+                        "Print all files inside of directories given as input, in the order of \
+                         their paths. Hidden files and files ignored by Git ('.gitignore') are \
+                         skipped. Without this option, directories are rejected.",
+                    ),
+            ).arg(
+                Arg::with_name("glob")
+                    .long("glob")
+                    .multiple(true)
+                    .number_of_values(1)
+                    .takes_value(true)
+                    .value_name("pattern")
+                    .requires("recursive")
+                    .help("Only print files inside of directories that match the pattern.")
+                    .long_help(
+                        "Only print files inside of directories that match the given glob \
+                         pattern (e.g. '*.rs'). The pattern is matched against the path \
+                         relative to the directory. Can be given multiple times.",
+                    ),
+            ).arg(
+                Arg::with_name("exclude")
+                    .long("exclude")
+                    .multiple(true)
+                    .number_of_values(1)
+                    .takes_value(true)
+                    .value_name("pattern")
+                    .requires("recursive")
+                    .help("Skip files inside of directories that match the pattern.")
+                    .long_help(
+                        "Skip files inside of directories that match the given glob pattern \
+                         (e.g. 'tests/**'). The pattern is matched against the path relative \
+                         to the directory. Can be given multiple times.",
                     ),
             ).arg(
                 Arg::with_name("color")
@@ -258,19 +575,43 @@ impl App {
                     .overrides_with("wrap")
                     .takes_value(true)
                     .value_name("mode")
-                    .possible_values(&["character", "never"])
+                    .possible_values(&["character", "word", "never"])
                     .default_value("character")
                     .help("Specify the text-wrapping mode.")
-                    .long_help("Specify the text-wrapping mode."),
+                    .long_help(
+                        "Specify the text-wrapping mode. The 'word' mode breaks lines at \
+                         whitespace and punctuation and only splits words that are too long \
+                         to fit into a single line.",
+                    ),
             ).arg(
                 Arg::with_name("unbuffered")
                     .short("u")
+                    .overrides_with("unbuffered")
                     .hidden_short_help(true)
                     .long_help(
                         "This option exists for POSIX-compliance reasons ('u' is for \
                          'unbuffered'). The output is always unbuffered - this option \
                          is simply ignored.",
                     ),
+            ).arg(
+                Arg::with_name("config-file")
+                    .long("config-file")
+                    .conflicts_with("generate-config-file")
+                    .help("Show path to the configuration file.")
+                    .long_help(
+                        "Show the path to the configuration file, which contains default \
+                         command-line options. It can be changed with the BAT_CONFIG_PATH \
+                         environment variable.",
+                    ),
+            ).arg(
+                Arg::with_name("generate-config-file")
+                    .long("generate-config-file")
+                    .help("Generate a default configuration file.")
+                    .long_help(
+                        "Write a default configuration file with commented-out examples to \
+                         the path shown by '--config-file'. An existing file is never \
+                         overwritten.",
+                    ),
             ).subcommand(
                 SubCommand::with_name("cache")
                     .about("Modify the syntax-definition and theme cache")
@@ -322,16 +663,48 @@ impl App {
                     ),
             ).help_message("Print this help message.")
             .version_message("Show version information.")
-            .get_matches()
+    }
+
+    /// The values of an option which can be given multiple times, without the ones from the
+    /// configuration file if the option is also given on the command line.
+    fn values_of(&self, option: &str) -> Option<Skip<Values>> {
+        let skipped = self.skipped_values.get(option).cloned().unwrap_or(0);
+        self.matches
+            .values_of(option)
+            .map(|values| values.skip(skipped))
     }
 
     pub fn config(&self) -> Result<Config> {
         let files = self.files();
+        let file_names = self.file_names();
+        if file_names.len() > files.len() {
+            return Err("More '--file-name' options than files were given".into());
+        }
+        let project_config = self.project_config();
 
         Ok(Config {
+            recursive: self.matches.is_present("recursive"),
+            file_filter: self.file_filter()?,
             true_color: is_truecolor_terminal(),
-            output_components: self.output_components()?,
+            output_components: self.output_components(project_config.style.as_ref())?,
+            diff_base: self.matches.value_of("diff-base"),
+            only_changed_lines: self.matches.is_present("diff"),
+            diff_context: self
+                .matches
+                .value_of("diff-context")
+                .map(|c| c.parse())
+                .unwrap_or(Ok(3))?,
+            inline_diff: self.matches.is_present("inline-diff"),
+            blame_heatmap: self.matches.is_present("blame-heatmap"),
             language: self.matches.value_of("language"),
+            syntax_mapping: self.syntax_mapping(project_config.syntax_mapping)?,
+            encoding: self.encoding()?,
+            tab_width: self
+                .matches
+                .value_of("tabs")
+                .map(|t| t.parse())
+                .unwrap_or(Ok(project_config.tab_width.unwrap_or(4)))?,
+            show_nonprintable: self.matches.is_present("show-all"),
             output_wrap: if !self.interactive_output {
                 // We don't have the tty width when piping to another program.
                 // There's no point in wrapping when this is the case.
@@ -339,6 +712,7 @@ impl App {
             } else {
                 match self.matches.value_of("wrap") {
                     Some("character") => OutputWrap::Character,
+                    Some("word") => OutputWrap::Word,
                     Some("never") | _ => OutputWrap::None,
                 }
             },
@@ -372,17 +746,73 @@ impl App {
                 || self.matches.value_of("color") == Some("always")
                 || self.matches.value_of("decorations") == Some("always")),
             files,
+            file_names,
             theme: self
                 .matches
                 .value_of("theme")
                 .map(String::from)
                 .or_else(|| env::var("BAT_THEME").ok())
+                .or(project_config.theme)
                 .unwrap_or(String::from(BAT_THEME_DEFAULT)),
-            line_range: transpose(self.matches.value_of("line-range").map(LineRange::from))?,
+            line_ranges: self.line_ranges()?,
+            highlighted_lines: self.highlighted_lines()?,
+            pattern: self.pattern()?,
+            only_matching_lines: self.matches.is_present("only-matching-lines"),
+            context: self
+                .matches
+                .value_of("context")
+                .map(|c| c.parse())
+                .unwrap_or(Ok(0))?,
         })
     }
 
+    /// The settings from the `.bat.toml` file of the Git repository we are in, if any. They
+    /// only apply to options which are not given on the command line or in the configuration
+    /// file. The repository is found from the current working directory, not from the inputs:
+    /// the settings are the same for all of them. Like a broken configuration file, a broken
+    /// project configuration is only reported.
+    fn project_config(&self) -> ProjectConfig {
+        self.read_project_config().unwrap_or_else(|error| {
+            print_warning(&error.to_string());
+            ProjectConfig::default()
+        })
+    }
+
+    fn read_project_config(&self) -> Result<ProjectConfig> {
+        let project_config = match env::current_dir() {
+            Ok(dir) => ProjectConfig::discover(&dir)?.unwrap_or_default(),
+            Err(_) => ProjectConfig::default(),
+        };
+
+        if let Some(ref style) = project_config.style {
+            validate_style(style).map_err(|error| {
+                format!(
+                    "Invalid project configuration file '{}': {}",
+                    project_config.path.to_string_lossy(),
+                    error
+                )
+            })?;
+        }
+
+        Ok(project_config)
+    }
+
+    /// The syntax mappings from the command line and the configuration file, which take
+    /// precedence over the ones from the project configuration.
+    fn syntax_mapping(&self, project_mapping: SyntaxMapping) -> Result<SyntaxMapping> {
+        let mut syntax_mapping = project_mapping;
+        if let Some(values) = self.values_of("map-syntax") {
+            for mapping in values {
+                syntax_mapping.insert_from_str(mapping)?;
+            }
+        }
+
+        Ok(syntax_mapping)
+    }
+
     fn files(&self) -> Vec<InputFile> {
+        let revision = self.matches.value_of("rev");
+
         self.matches
             .values_of("FILE")
             .map(|values| {
@@ -390,6 +820,10 @@ impl App {
                     .map(|filename| {
                         if filename == "-" {
                             InputFile::StdIn
+                        } else if let Some((revision, path)) = split_revision(filename) {
+                            InputFile::Revision(revision, path)
+                        } else if let Some(revision) = revision {
+                            InputFile::Revision(revision, filename)
                         } else {
                             InputFile::Ordinary(filename)
                         }
@@ -397,7 +831,64 @@ impl App {
             }).unwrap_or_else(|| vec![InputFile::StdIn])
     }
 
-    fn output_components(&self) -> Result<OutputComponents> {
+    fn file_names(&self) -> Vec<Option<&str>> {
+        self.values_of("file-name")
+            .map(|values| values.map(Some).collect())
+            .unwrap_or_default()
+    }
+
+    fn file_filter(&self) -> Result<FileFilter> {
+        let include: Vec<&str> = self
+            .values_of("glob")
+            .map(|values| values.collect())
+            .unwrap_or_default();
+        let exclude: Vec<&str> = self
+            .values_of("exclude")
+            .map(|values| values.collect())
+            .unwrap_or_default();
+
+        FileFilter::new(&include, &exclude)
+    }
+
+    fn encoding(&self) -> Result<Option<&'static Encoding>> {
+        match self.matches.value_of("encoding") {
+            Some(label) => Encoding::for_label(label.as_bytes())
+                .map(Some)
+                .ok_or_else(|| format!("Unknown encoding '{}'", label).into()),
+            None => Ok(None),
+        }
+    }
+
+    fn line_ranges(&self) -> Result<LineRanges> {
+        Ok(match self.values_of("line-range") {
+            Some(values) => LineRanges::from(
+                values
+                    .map(LineRange::from)
+                    .collect::<Result<Vec<LineRange>>>()?,
+            ),
+            None => LineRanges::all(),
+        })
+    }
+
+    fn pattern(&self) -> Result<Option<Regex>> {
+        Ok(match self.matches.value_of("pattern") {
+            Some(pattern) => Some(Regex::new(pattern)?),
+            None => None,
+        })
+    }
+
+    fn highlighted_lines(&self) -> Result<LineRanges> {
+        Ok(match self.values_of("highlight-line") {
+            Some(values) => LineRanges::from(
+                values
+                    .map(LineRange::from)
+                    .collect::<Result<Vec<LineRange>>>()?,
+            ),
+            None => LineRanges::none(),
+        })
+    }
+
+    fn output_components(&self, project_style: Option<&String>) -> Result<OutputComponents> {
         let matches = &self.matches;
         Ok(OutputComponents(
             if matches.value_of("decorations") == Some("never") {
@@ -407,7 +898,17 @@ impl App {
             } else if matches.is_present("plain") {
                 [OutputComponent::Plain].iter().cloned().collect()
             } else {
-                values_t!(matches.values_of("style"), OutputComponent)?
+                let style = if matches.occurrences_of("style") > 0 {
+                    matches.value_of("style")
+                } else {
+                    project_style.map(String::as_str)
+                };
+
+                style
+                    .unwrap_or("auto")
+                    .split(',')
+                    .map(|style| style.parse::<OutputComponent>())
+                    .collect::<Result<Vec<_>>>()?
                     .into_iter()
                     .map(|style| style.components(self.interactive_output))
                     .fold(HashSet::new(), |mut acc, components| {
@@ -418,3 +919,17 @@ impl App {
         ))
     }
 }
+
+#[test]
+fn test_split_revision() {
+    assert_eq!(
+        Some(("HEAD~2", "src/main.rs")),
+        split_revision("HEAD~2:src/main.rs")
+    );
+    assert_eq!(Some(("v0.5.0", "a:b")), split_revision("v0.5.0:a:b"));
+    assert_eq!(None, split_revision("src/main.rs"));
+    assert_eq!(None, split_revision(":src/main.rs"));
+    assert_eq!(None, split_revision("HEAD:"));
+    assert_eq!(None, split_revision("C:\\src\\main.rs"));
+    assert_eq!(None, split_revision("d:/src/main.rs"));
+}
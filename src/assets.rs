@@ -11,7 +11,9 @@ use syntect::parsing::{SyntaxDefinition, SyntaxSet};
 #[cfg(unix)]
 use std::os::unix::fs::FileTypeExt;
 
-use app::InputFile;
+use inputfile::{InputFile, InputFileReader};
+use modeline::{self, MODELINE_LINES};
+use syntax_mapping::SyntaxMapping;
 
 lazy_static! {
     static ref PROJECT_DIRS: ProjectDirs =
@@ -166,34 +168,125 @@ impl HighlightingAssets {
         }
     }
 
-    pub fn get_syntax(&self, language: Option<&str>, filename: InputFile) -> &SyntaxDefinition {
+    pub fn get_syntax(
+        &self,
+        language: Option<&str>,
+        syntax_mapping: &SyntaxMapping,
+        filename: InputFile,
+        file_name: Option<&str>,
+        reader: &InputFileReader,
+    ) -> &SyntaxDefinition {
+        // A name given with `--file-name` replaces the actual file name. For compressed input, the
+        // name of the file inside is used.
+        let name = match (file_name, filename) {
+            (Some(name), _)
+            | (None, InputFile::Ordinary(name))
+            | (None, InputFile::Revision(_, name)) => Some(match reader.compression {
+                Some(compression) => compression.inner_file_name(name),
+                None => name,
+            }),
+            _ => None,
+        };
+
+        // User-defined mappings take precedence over the built-in detection, but not over an
+        // explicitly configured language.
+        let language =
+            language.or_else(|| name.and_then(|name| syntax_mapping.get_language(Path::new(name))));
+
+        // Modelines rank above the detection from the file name or the first line.
+        let modeline_syntax = match language {
+            Some(_) => None,
+            None => self.find_syntax_by_modeline(filename, reader),
+        };
+
         let syntax = match (language, filename) {
             (Some(language), _) => self.syntax_set.find_syntax_by_token(language),
+            (None, _) if modeline_syntax.is_some() => modeline_syntax,
+            (None, InputFile::Ordinary(_)) | (None, InputFile::StdIn)
+                if file_name.is_some() || reader.compression.is_some() =>
+            {
+                // The actual file can not be used to detect the syntax, so look at the name and at
+                // the first line of the (decompressed) input.
+                name.and_then(|name| self.find_syntax_by_file_name(name))
+                    .or_else(|| self.find_syntax_by_first_line(reader))
+            }
+            (None, InputFile::Revision(..)) => name
+                .and_then(|name| self.find_syntax_by_file_name(name))
+                .or_else(|| self.find_syntax_by_first_line(reader)),
             (None, InputFile::Ordinary(filename)) => {
-                #[cfg(not(unix))]
-                let may_read_from_file = true;
-
-                // Do not peek at the file (to determine the syntax) if it is a FIFO because they can
-                // only be read once.
-                #[cfg(unix)]
-                let may_read_from_file = !fs::metadata(filename)
-                    .map(|m| m.file_type().is_fifo())
-                    .unwrap_or(false);
-
-                if may_read_from_file {
+                if may_read_from_file(filename) {
                     self.syntax_set
                         .find_syntax_for_file(filename)
                         .unwrap_or(None)
                 } else {
-                    None
+                    self.find_syntax_by_file_name(filename)
+                        .or_else(|| self.find_syntax_by_first_line(reader))
                 }
             }
-            (None, InputFile::StdIn) => None,
+            (None, InputFile::StdIn) => self.find_syntax_by_first_line(reader),
             (_, InputFile::ThemePreviewFile) => self.syntax_set.find_syntax_by_name("Rust"),
         };
 
         syntax.unwrap_or_else(|| self.syntax_set.find_syntax_plain_text())
     }
+
+    /// Detect the syntax from a Vim or Emacs modeline in the first or last lines of the input.
+    /// The last lines can only be read from regular, uncompressed files.
+    fn find_syntax_by_modeline(
+        &self,
+        filename: InputFile,
+        reader: &InputFileReader,
+    ) -> Option<&SyntaxDefinition> {
+        let last_lines = match filename {
+            InputFile::Ordinary(filename)
+                if reader.compression.is_none() && may_read_from_file(filename) =>
+            {
+                modeline::last_lines(Path::new(filename), MODELINE_LINES, reader.encoding)
+                    .unwrap_or_default()
+            }
+            _ => vec![],
+        };
+
+        modeline::find_filetype(reader.first_lines.iter().chain(&last_lines)).and_then(
+            |filetype| {
+                self.syntax_set
+                    .find_syntax_by_token(modeline::syntax_token(filetype))
+            },
+        )
+    }
+
+    /// Detect the syntax from the first line of the input (like a shebang), if it has been
+    /// peeked at.
+    fn find_syntax_by_first_line(&self, reader: &InputFileReader) -> Option<&SyntaxDefinition> {
+        reader
+            .first_lines
+            .first()
+            .and_then(|line| self.syntax_set.find_syntax_by_first_line(line))
+    }
+
+    fn find_syntax_by_file_name(&self, filename: &str) -> Option<&SyntaxDefinition> {
+        let path = Path::new(filename);
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let extension = path.extension().and_then(|x| x.to_str()).unwrap_or("");
+
+        self.syntax_set
+            .find_syntax_by_extension(file_name)
+            .or_else(|| self.syntax_set.find_syntax_by_extension(extension))
+    }
+}
+
+/// Do not peek at the file (to determine the syntax) if it is a FIFO because they can only be
+/// read once.
+#[cfg(unix)]
+fn may_read_from_file(filename: &str) -> bool {
+    !fs::metadata(filename)
+        .map(|m| m.file_type().is_fifo())
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn may_read_from_file(_filename: &str) -> bool {
+    true
 }
 
 // TODO: this function will soon be part of syntect's `ThemeSet`.
@@ -0,0 +1,376 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use git2::{Blame, BlameOptions, Diff, Oid, Repository, Signature};
+
+use diff::{open_repository, single_file_diff_options};
+
+/// The file at the root of a repository which lists commits that should be skipped by blame
+/// (like reformatting), unless the Git option 'blame.ignoreRevsFile' names another one
+pub const IGNORE_REVS_FILE_NAME: &str = ".git-blame-ignore-revs";
+
+/// The maximum number of ignored commits in a row that are looked through for a single line
+const MAX_IGNORED_DEPTH: usize = 10;
+
+/// The commit which last changed a line
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommitInfo {
+    /// The abbreviated commit id
+    pub short_id: String,
+
+    /// The initials of the author
+    pub initials: String,
+
+    /// The time of the commit, in seconds since the Unix epoch
+    pub time: i64,
+}
+
+/// The last commit for every line of a file. The blame is computed only once per file, when it is
+/// opened, and every commit is looked up only once.
+pub struct LineBlames {
+    /// The commit for every line of the file in `HEAD`
+    lines: HashMap<u32, Oid>,
+    commits: HashMap<Oid, CommitInfo>,
+
+    /// The changes of the file in the working tree compared to `HEAD`
+    hunks: Vec<Hunk>,
+
+    /// The times of the oldest and the newest commit of the file, for `relative_age`
+    oldest: i64,
+    newest: i64,
+
+    /// The time at which the blame was computed, in seconds since the Unix epoch
+    pub now: i64,
+}
+
+impl LineBlames {
+    /// The commit which last changed the line `line_number` of the file in the working tree.
+    /// This is `None` for lines which have not been committed yet.
+    pub fn get(&self, line_number: u32) -> Option<&CommitInfo> {
+        let line_number = self.line_in_head(line_number)?;
+        self.lines
+            .get(&line_number)
+            .and_then(|oid| self.commits.get(oid))
+    }
+
+    /// The position of a commit between the oldest (1.0) and the newest (0.0) commit of the file
+    pub fn relative_age(&self, commit: &CommitInfo) -> f64 {
+        if self.newest > self.oldest {
+            (self.newest - commit.time) as f64 / (self.newest - self.oldest) as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// The number of the line in the `HEAD` version of the file, if it is unchanged.
+    fn line_in_head(&self, line_number: u32) -> Option<u32> {
+        old_line_number(&self.hunks, line_number, false)
+    }
+}
+
+/// A change between two versions of a file, as (old start, old lines, new start, new lines)
+type Hunk = (u32, u32, u32, u32);
+
+/// The number of the line `line_number` of the new version of a file in the old version, given
+/// the hunks of the diff between them. Changed lines only have a number in the old version if
+/// `follow_changes` is set: they are matched to the old lines of the hunk by their position.
+fn old_line_number(hunks: &[Hunk], line_number: u32, follow_changes: bool) -> Option<u32> {
+    let mut offset: i64 = 0;
+
+    for &(old_start, old_lines, new_start, new_lines) in hunks {
+        // For pure deletions, `new_start` is the line after which the lines were removed.
+        let new_end = if new_lines == 0 {
+            new_start + 1
+        } else {
+            new_start + new_lines
+        };
+
+        if line_number < new_start || (new_lines == 0 && line_number == new_start) {
+            break;
+        }
+        if line_number < new_end {
+            let position = line_number - new_start;
+            return if follow_changes && position < old_lines {
+                Some(old_start + position)
+            } else {
+                None
+            };
+        }
+
+        offset += i64::from(new_lines) - i64::from(old_lines);
+    }
+
+    Some((i64::from(line_number) - offset) as u32)
+}
+
+/// The hunks of a diff which contains a single file
+fn diff_hunks(diff: &Diff) -> Vec<Hunk> {
+    let mut hunks = vec![];
+    let _ = diff.foreach(
+        &mut |_, _| true,
+        None,
+        Some(&mut |_, hunk| {
+            hunks.push((
+                hunk.old_start(),
+                hunk.old_lines(),
+                hunk.new_start(),
+                hunk.new_lines(),
+            ));
+            true
+        }),
+        None,
+    );
+    hunks
+}
+
+/// Blame the file `filename`, skipping the commits listed in the ignore file of the repository.
+pub fn get_blame(filename: &str) -> Option<LineBlames> {
+    let (repo, path_relative_to_repo) = open_repository(filename)?;
+    let path_relative_to_repo = path_relative_to_repo.as_path();
+
+    let head = repo.head().ok()?.peel_to_tree().ok()?;
+    let mut diff_options = single_file_diff_options(path_relative_to_repo)?;
+    let diff = repo
+        .diff_tree_to_workdir(Some(&head), Some(&mut diff_options))
+        .ok()?;
+
+    let hunks = diff_hunks(&diff);
+
+    let ignored = ignored_revisions(&repo);
+    let mut blamer = Blamer {
+        repo: &repo,
+        path: path_relative_to_repo,
+        blames: HashMap::new(),
+        hunks: HashMap::new(),
+    };
+    let blame = repo.blame_file(path_relative_to_repo, None).ok()?;
+
+    let mut lines = HashMap::new();
+    let mut commits = HashMap::new();
+    for hunk in blame.iter() {
+        for index in 0..hunk.lines_in_hunk() {
+            let mut oid = hunk.final_commit_id();
+            let mut info = None;
+
+            // Look through ignored commits at the same line of the version before them.
+            let mut line = hunk.orig_start_line() + index;
+            for _ in 0..MAX_IGNORED_DEPTH {
+                if !ignored.contains(&oid) {
+                    break;
+                }
+                match blamer.blame_before(oid, line) {
+                    Some((parent_oid, parent_line, parent_info)) => {
+                        oid = parent_oid;
+                        line = parent_line;
+                        info = Some(parent_info);
+                    }
+                    None => break,
+                }
+            }
+
+            lines.insert((hunk.final_start_line() + index) as u32, oid);
+            commits.entry(oid).or_insert_with(|| {
+                info.unwrap_or_else(|| commit_info(oid, &hunk.final_signature()))
+            });
+        }
+    }
+
+    let times = commits.values().map(|commit| commit.time);
+    let oldest = times.clone().min().unwrap_or(0);
+    let newest = times.max().unwrap_or(0);
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0);
+
+    Some(LineBlames {
+        lines,
+        commits,
+        hunks,
+        oldest,
+        newest,
+        now,
+    })
+}
+
+/// Blames of the file in older revisions, which are needed to look through ignored commits
+struct Blamer<'a> {
+    repo: &'a Repository,
+    path: &'a Path,
+    blames: HashMap<Oid, Blame<'a>>,
+
+    /// The changes of the file in ignored commits, compared to their parents
+    hunks: HashMap<Oid, Vec<Hunk>>,
+}
+
+impl<'a> Blamer<'a> {
+    /// The commit which last changed the line `line` of the file before the commit `oid`, and
+    /// the number of the line in that commit.
+    fn blame_before(&mut self, oid: Oid, line: usize) -> Option<(Oid, usize, CommitInfo)> {
+        let commit = self.repo.find_commit(oid).ok()?;
+        let parent = commit.parent(0).ok()?;
+        let parent_id = parent.id();
+
+        // The line may have moved, or it may have been changed (e.g. reformatted) by the commit.
+        if !self.hunks.contains_key(&oid) {
+            let mut diff_options = single_file_diff_options(self.path)?;
+            let diff = self
+                .repo
+                .diff_tree_to_tree(
+                    Some(&parent.tree().ok()?),
+                    Some(&commit.tree().ok()?),
+                    Some(&mut diff_options),
+                ).ok()?;
+            self.hunks.insert(oid, diff_hunks(&diff));
+        }
+        let line = old_line_number(&self.hunks[&oid], line as u32, true)? as usize;
+
+        if !self.blames.contains_key(&parent_id) {
+            let mut options = BlameOptions::new();
+            options.newest_commit(parent_id);
+            let blame = self.repo.blame_file(self.path, Some(&mut options)).ok()?;
+            self.blames.insert(parent_id, blame);
+        }
+
+        let hunk = self.blames[&parent_id].get_line(line)?;
+        let info = commit_info(hunk.final_commit_id(), &hunk.final_signature());
+
+        Some((
+            hunk.final_commit_id(),
+            hunk.orig_start_line() + (line - hunk.final_start_line()),
+            info,
+        ))
+    }
+}
+
+fn commit_info(oid: Oid, author: &Signature) -> CommitInfo {
+    CommitInfo {
+        short_id: oid.to_string().chars().take(7).collect(),
+        initials: initials(author.name().unwrap_or("")),
+        time: author.when().seconds(),
+    }
+}
+
+/// The commits listed in the ignore file of the repository, one per line. Empty lines and
+/// comments starting with '#' are skipped.
+fn ignored_revisions(repo: &Repository) -> HashSet<Oid> {
+    let path = repo
+        .config()
+        .and_then(|config| config.get_path("blame.ignoreRevsFile"))
+        .unwrap_or_else(|_| PathBuf::from(IGNORE_REVS_FILE_NAME));
+    let path = match repo.workdir() {
+        Some(workdir) => workdir.join(path),
+        None => return HashSet::new(),
+    };
+
+    let mut contents = String::new();
+    if File::open(&path)
+        .and_then(|mut file| file.read_to_string(&mut contents))
+        .is_err()
+    {
+        return HashSet::new();
+    }
+
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|revision| !revision.is_empty())
+        .filter_map(|revision| repo.revparse_single(revision).ok())
+        .map(|object| object.id())
+        .collect()
+}
+
+/// The initials of an author, at most three letters.
+pub fn initials(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .flat_map(char::to_uppercase)
+        .take(3)
+        .collect()
+}
+
+/// A short description of a duration in seconds, like '3 weeks'.
+pub fn format_age(seconds: i64) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    const WEEK: i64 = 7 * DAY;
+    const MONTH: i64 = 30 * DAY;
+    const YEAR: i64 = 365 * DAY;
+
+    let (count, unit) = match seconds {
+        s if s < MINUTE => return "now".to_owned(),
+        s if s < HOUR => (s / MINUTE, "min"),
+        s if s < DAY => (s / HOUR, "hour"),
+        s if s < WEEK => (s / DAY, "day"),
+        s if s < MONTH => (s / WEEK, "week"),
+        s if s < YEAR => (s / MONTH, "month"),
+        s => (s / YEAR, "year"),
+    };
+
+    if count == 1 || unit == "min" {
+        format!("{} {}", count, unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+#[test]
+fn test_initials() {
+    assert_eq!("DP", initials("David Peter"));
+    assert_eq!("JRR", initials("J. R. R. Tolkien"));
+    assert_eq!("É", initials("émile"));
+    assert_eq!("", initials(""));
+}
+
+#[test]
+fn test_format_age() {
+    assert_eq!("now", format_age(5));
+    assert_eq!("5 min", format_age(5 * 60));
+    assert_eq!("1 hour", format_age(90 * 60));
+    assert_eq!("3 days", format_age(3 * 24 * 3600));
+    assert_eq!("2 weeks", format_age(15 * 24 * 3600));
+    assert_eq!("11 months", format_age(340 * 24 * 3600));
+    assert_eq!("4 years", format_age(4 * 366 * 24 * 3600));
+}
+
+#[test]
+fn test_line_in_head() {
+    let blames = LineBlames {
+        lines: HashMap::new(),
+        commits: HashMap::new(),
+        // Line 2 modified, line 5 removed and two lines added after line 8 (of the old file)
+        hunks: vec![(2, 1, 2, 1), (5, 1, 4, 0), (8, 0, 8, 2)],
+        oldest: 0,
+        newest: 0,
+        now: 0,
+    };
+
+    assert_eq!(Some(1), blames.line_in_head(1));
+    assert_eq!(None, blames.line_in_head(2));
+    assert_eq!(Some(3), blames.line_in_head(3));
+    assert_eq!(Some(4), blames.line_in_head(4));
+    assert_eq!(Some(6), blames.line_in_head(5));
+    assert_eq!(Some(7), blames.line_in_head(6));
+    assert_eq!(None, blames.line_in_head(8));
+    assert_eq!(None, blames.line_in_head(9));
+    assert_eq!(Some(9), blames.line_in_head(10));
+}
+
+#[test]
+fn test_old_line_number_follow_changes() {
+    // Two lines replaced by three lines, and one line added at the start
+    let hunks = vec![(0, 0, 1, 1), (3, 2, 4, 3)];
+
+    assert_eq!(None, old_line_number(&hunks, 1, true));
+    assert_eq!(Some(2), old_line_number(&hunks, 3, true));
+    assert_eq!(Some(3), old_line_number(&hunks, 4, true));
+    assert_eq!(Some(4), old_line_number(&hunks, 5, true));
+    assert_eq!(None, old_line_number(&hunks, 6, true));
+    assert_eq!(None, old_line_number(&hunks, 5, false));
+    assert_eq!(Some(5), old_line_number(&hunks, 7, true));
+}
@@ -0,0 +1,148 @@
+use std::io::{self, BufRead, BufReader};
+
+use bzip2::bufread::MultiBzDecoder;
+use flate2::bufread::MultiGzDecoder;
+use xz2::bufread::XzDecoder;
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+/// Compression formats that are transparently decompressed, detected by their magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+impl Compression {
+    pub fn detect(header: &[u8]) -> Option<Compression> {
+        if header.starts_with(b"\x1f\x8b") {
+            Some(Compression::Gzip)
+        } else if is_bzip2(header) {
+            Some(Compression::Bzip2)
+        } else if header.starts_with(b"\xfd7zXZ\x00") {
+            Some(Compression::Xz)
+        } else if header.starts_with(b"\x28\xb5\x2f\xfd") {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Compression::Gzip => "gzip",
+            Compression::Bzip2 => "bzip2",
+            Compression::Xz => "xz",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match *self {
+            Compression::Gzip => ".gz",
+            Compression::Bzip2 => ".bz2",
+            Compression::Xz => ".xz",
+            Compression::Zstd => ".zst",
+        }
+    }
+
+    /// The name of the compressed file, i.e. `filename` without the extension of the compression
+    /// format (`data.json.gz` is `data.json`).
+    pub fn inner_file_name<'a>(&self, filename: &'a str) -> &'a str {
+        let extension = self.extension();
+        if filename.len() > extension.len() && filename.ends_with(extension) {
+            &filename[..filename.len() - extension.len()]
+        } else {
+            filename
+        }
+    }
+
+    pub fn decoder<'a>(&self, reader: Box<BufRead + 'a>) -> io::Result<Box<BufRead + 'a>> {
+        Ok(match *self {
+            Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+            Compression::Bzip2 => Box::new(BufReader::new(MultiBzDecoder::new(reader))),
+            Compression::Xz => Box::new(BufReader::new(XzDecoder::new_multi_decoder(reader))),
+            Compression::Zstd => Box::new(BufReader::new(ZstdDecoder::with_buffer(reader)?)),
+        })
+    }
+}
+
+/// A bzip2 stream starts with 'BZh', the block size ('1' to '9') and the magic number of the
+/// first block, or of the end of the stream if it is empty.
+fn is_bzip2(header: &[u8]) -> bool {
+    if header.len() < 10 || !header.starts_with(b"BZh") {
+        return false;
+    }
+
+    match header[3] {
+        b'1'..=b'9' => &header[4..10] == b"1AY&SY" || &header[4..10] == b"\x17rE8P\x90",
+        _ => false,
+    }
+}
+
+#[test]
+fn test_detect() {
+    assert_eq!(
+        Some(Compression::Gzip),
+        Compression::detect(b"\x1f\x8b\x08\x00")
+    );
+    assert_eq!(
+        Some(Compression::Bzip2),
+        Compression::detect(b"BZh91AY&SY\x00")
+    );
+    assert_eq!(
+        Some(Compression::Xz),
+        Compression::detect(b"\xfd7zXZ\x00\x00")
+    );
+    assert_eq!(
+        Some(Compression::Zstd),
+        Compression::detect(b"\x28\xb5\x2f\xfd\x00")
+    );
+    assert_eq!(None, Compression::detect(b"fn main() {}"));
+    assert_eq!(None, Compression::detect(b""));
+}
+
+#[test]
+fn test_detect_bzip2() {
+    assert_eq!(
+        Some(Compression::Bzip2),
+        Compression::detect(b"BZh1\x17rE8P\x90\x00\x00\x00\x00")
+    );
+    assert_eq!(None, Compression::detect(b"BZh is not a bzip2 file"));
+    assert_eq!(None, Compression::detect(b"BZh01AY&SY"));
+    assert_eq!(None, Compression::detect(b"BZh91AY"));
+}
+
+#[test]
+fn test_inner_file_name() {
+    assert_eq!(
+        "data.json",
+        Compression::Gzip.inner_file_name("data.json.gz")
+    );
+    assert_eq!(
+        "logs/app.log",
+        Compression::Zstd.inner_file_name("logs/app.log.zst")
+    );
+    assert_eq!("data.gz", Compression::Xz.inner_file_name("data.gz"));
+    assert_eq!(".gz", Compression::Gzip.inner_file_name(".gz"));
+}
+
+#[test]
+fn test_gzip_roundtrip() {
+    use flate2::write::GzEncoder;
+    use std::io::{Read, Write};
+
+    let mut encoder = GzEncoder::new(Vec::new(), ::flate2::Compression::default());
+    encoder.write_all(b"{\"key\": 42}\n").unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let reader: Box<BufRead> = Box::new(&compressed[..]);
+    let mut decompressed = String::new();
+    Compression::Gzip
+        .decoder(reader)
+        .unwrap()
+        .read_to_string(&mut decompressed)
+        .unwrap();
+    assert_eq!("{\"key\": 42}\n", decompressed);
+}
@@ -0,0 +1,101 @@
+use encoding_rs::Encoding;
+
+use regex::Regex;
+
+use inputfile::InputFile;
+use line_range::LineRanges;
+use style::{OutputComponents, OutputWrap};
+use syntax_mapping::SyntaxMapping;
+use walk::FileFilter;
+
+#[derive(Debug, Clone, Copy)]
+pub enum PagingMode {
+    Always,
+    QuitIfOneScreen,
+    Never,
+}
+
+#[derive(Clone)]
+pub struct Config<'a> {
+    /// List of files to print
+    pub files: Vec<InputFile<'a>>,
+
+    /// Names which replace the actual file names in the header and for the syntax detection,
+    /// matched to `files` by position
+    pub file_names: Vec<Option<&'a str>>,
+
+    /// Whether or not to print the files inside of directories given as input
+    pub recursive: bool,
+
+    /// Which files inside of directories to print
+    pub file_filter: FileFilter,
+
+    /// The explicitly configured language, if any
+    pub language: Option<&'a str>,
+
+    /// User-defined syntaxes for files matching certain patterns
+    pub syntax_mapping: SyntaxMapping,
+
+    /// The encoding of input files without a byte order mark (default: UTF-8)
+    pub encoding: Option<&'static Encoding>,
+
+    /// The character width of the terminal
+    pub term_width: usize,
+
+    /// Whether or not to simply loop through all input (`cat` mode)
+    pub loop_through: bool,
+
+    /// Whether or not the output should be colorized
+    pub colored_output: bool,
+
+    /// Whether or not the output terminal supports true color
+    pub true_color: bool,
+
+    /// Style elements (grid, line numbers, ...)
+    pub output_components: OutputComponents,
+
+    /// The revision to compare files with for the Git modification markers, instead of `HEAD`
+    pub diff_base: Option<&'a str>,
+
+    /// Whether or not to only print the lines that have been modified (and their context)
+    pub only_changed_lines: bool,
+
+    /// The number of lines to print before and after every modified line
+    pub diff_context: usize,
+
+    /// Whether or not to print the lines that have been removed, in between the current lines
+    pub inline_diff: bool,
+
+    /// Whether or not to color the blame of every line by its age
+    pub blame_heatmap: bool,
+
+    /// The width of a tab stop, or zero to print tabs as they are
+    pub tab_width: usize,
+
+    /// Whether or not to show non-printable characters as visible glyphs
+    pub show_nonprintable: bool,
+
+    /// Text wrapping mode
+    pub output_wrap: OutputWrap,
+
+    /// Pager or STDOUT
+    pub paging_mode: PagingMode,
+
+    /// The ranges of lines that should be printed
+    pub line_ranges: LineRanges,
+
+    /// The ranges of lines that should be highlighted with a background
+    pub highlighted_lines: LineRanges,
+
+    /// The pattern whose matches should be highlighted, if any
+    pub pattern: Option<Regex>,
+
+    /// Whether or not to only print the lines that match the pattern (and their context)
+    pub only_matching_lines: bool,
+
+    /// The number of lines to print before and after every matching line
+    pub context: usize,
+
+    /// The syntax highlighting theme
+    pub theme: String,
+}
@@ -0,0 +1,140 @@
+use std::env;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use shell_words;
+
+use bat::assets::config_dir;
+use bat::errors::*;
+
+const DEFAULT_CONFIG_FILE: &str = "\
+# This is bat's configuration file. Every line either contains a comment or
+# command-line options that should be passed to bat by default. Options given
+# on the command line always take precedence. Run 'bat --help' to get a list
+# of all possible options.
+
+# Specify the highlighting theme. Run 'bat --list-themes' for a list of all
+# available themes.
+#--theme=\"TwoDark\"
+
+# Show line numbers, Git modifications and file headers (but no grid)
+#--style=\"numbers,changes,header\"
+
+# Highlight files with certain names using a specific syntax
+#--map-syntax=\"*.conf:INI\"
+#--map-syntax=\"Jenkinsfile:Groovy\"
+
+# Use spaces for tabs, with a width of 8 columns
+#--tabs=8
+
+# Uncomment the following line to disable automatic paging:
+#--paging=never
+";
+
+/// The path of the configuration file, which can be changed with the `BAT_CONFIG_PATH`
+/// environment variable.
+pub fn config_file() -> PathBuf {
+    env::var_os("BAT_CONFIG_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(&*config_dir()).join("config"))
+}
+
+/// Read the default arguments from the configuration file. A missing file is not an error.
+pub fn get_args_from_config_file() -> Result<Vec<OsString>> {
+    let path = config_file();
+
+    let mut contents = String::new();
+    match File::open(&path).and_then(|mut file| file.read_to_string(&mut contents)) {
+        Ok(_) => {}
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(error) => {
+            return Err(format!(
+                "Can not read the configuration file '{}': {}",
+                path.to_string_lossy(),
+                error
+            ).into())
+        }
+    };
+
+    get_args_from_str(&contents).map_err(|error| {
+        format!(
+            "Invalid configuration file '{}': {}",
+            path.to_string_lossy(),
+            error
+        ).into()
+    })
+}
+
+/// Split the contents of a configuration file into arguments, like a shell would.
+fn get_args_from_str(contents: &str) -> Result<Vec<OsString>> {
+    let mut args = vec![];
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let words = shell_words::split(line)
+            .map_err(|_| format!("Unbalanced quotes in the line '{}'", line))?;
+        args.extend(words.into_iter().map(OsString::from));
+    }
+
+    Ok(args)
+}
+
+/// Write a default configuration file with commented-out examples. An existing configuration
+/// file is never overwritten.
+pub fn generate_config_file() -> Result<PathBuf> {
+    let path = config_file();
+
+    if path.exists() {
+        return Err(format!(
+            "The configuration file '{}' already exists.",
+            path.to_string_lossy()
+        ).into());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    File::create(&path)?.write_all(DEFAULT_CONFIG_FILE.as_bytes())?;
+
+    Ok(path)
+}
+
+#[test]
+fn test_get_args_from_str() {
+    let contents = "
+        # A comment
+        --theme=\"Sublime Snazzy\"
+
+        --style numbers,changes -A
+        #--paging=never
+        --pattern 'foo bar'
+    ";
+
+    assert_eq!(
+        vec![
+            "--theme=Sublime Snazzy",
+            "--style",
+            "numbers,changes",
+            "-A",
+            "--pattern",
+            "foo bar",
+        ],
+        get_args_from_str(contents).unwrap()
+    );
+}
+
+#[test]
+fn test_get_args_from_str_unbalanced_quotes() {
+    assert!(get_args_from_str("--theme=\"TwoDark").is_err());
+}
+
+#[test]
+fn test_default_config_file_has_no_active_args() {
+    assert!(get_args_from_str(DEFAULT_CONFIG_FILE).unwrap().is_empty());
+}
@@ -1,14 +1,17 @@
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, Write};
+use std::io::{self, Write};
+use std::path::Path;
 
-use app::{Config, InputFile};
 use assets::HighlightingAssets;
+use config::Config;
+use diff::{get_git_diff, LineChanges};
 use errors::*;
-use line_range::LineRange;
+use inputfile::{InputFile, InputFileReader};
+use line_range::{LineRange, LineRanges, RangeCheckResult};
+use modeline::MODELINE_LINES;
 use output::OutputType;
 use printer::{InteractivePrinter, Printer, SimplePrinter};
-
-const THEME_PREVIEW_FILE: &[u8] = include_bytes!("../assets/theme_preview.rs");
+use revision::check_revision;
+use walk;
 
 pub struct Controller<'a> {
     config: &'a Config<'a>,
@@ -23,80 +26,217 @@ impl<'b> Controller<'b> {
     pub fn run(&self) -> Result<bool> {
         let mut output_type = OutputType::from_mode(self.config.paging_mode);
         let writer = output_type.handle()?;
+        self.run_with_writer(writer)
+    }
+
+    /// Print all input files into the given `writer`, ignoring the configured paging mode.
+    pub fn run_with_writer(&self, writer: &mut Write) -> Result<bool> {
+        // A mistyped base would otherwise silently hide all modifications.
+        if let Some(base) = self.config.diff_base {
+            check_revision(base)?;
+        }
+
         let mut no_errors: bool = true;
 
-        for filename in &self.config.files {
-            let result = if self.config.loop_through {
-                let mut printer = SimplePrinter::new();
-                self.print_file(&mut printer, writer, *filename)
-            } else {
-                let mut printer = InteractivePrinter::new(&self.config, &self.assets, *filename);
-                self.print_file(&mut printer, writer, *filename)
+        let stdin = io::stdin();
+
+        for (index, input_file) in self.config.files.iter().enumerate() {
+            let file_name = self.config.file_names.get(index).and_then(|name| *name);
+
+            let mut report = |result: Result<()>| {
+                if let Err(error) = result {
+                    handle_error(&error);
+                    no_errors = false;
+                }
             };
 
-            if let Err(error) = result {
-                handle_error(&error);
-                no_errors = false;
+            match *input_file {
+                InputFile::Ordinary(path) if self.config.recursive && Path::new(path).is_dir() => {
+                    for file in walk::files(path, &self.config.file_filter) {
+                        report(file.and_then(|file| {
+                            self.print_input(writer, &stdin, InputFile::Ordinary(&file), None)
+                        }));
+                    }
+                }
+                _ => report(self.print_input(writer, &stdin, *input_file, file_name)),
             }
         }
 
         Ok(no_errors)
     }
 
+    fn print_input<'a>(
+        &self,
+        writer: &mut Write,
+        stdin: &'a io::Stdin,
+        input_file: InputFile<'a>,
+        file_name: Option<&str>,
+    ) -> Result<()> {
+        // The Git modifications are needed to select the lines in diff mode and for the markers
+        // of the interactive printer. The repository is only looked at once.
+        let line_changes = match input_file {
+            InputFile::Ordinary(filename)
+                if self.config.only_changed_lines
+                    || (!self.config.loop_through && self.config.output_components.changes()) =>
+            {
+                get_git_diff(filename, self.config.diff_base)
+            }
+            _ => None,
+        };
+
+        // In diff mode, files without modifications (or outside of a repository) are skipped.
+        let changed_lines = self.changed_lines(line_changes.as_ref());
+        if let Some(ref changed_lines) = changed_lines {
+            if changed_lines.ranges().is_empty() {
+                return Ok(());
+            }
+        }
+
+        let mut reader = input_file.get_reader(stdin)?;
+
+        // Input is printed uncompressed and as UTF-8, also when piping to another program (the
+        // interactive printer only deals with UTF-8).
+        reader.decompress()?;
+        reader.decode(self.config.encoding)?;
+
+        if !self.config.loop_through {
+            // The syntax may be detected from modelines or from the first line (e.g. a shebang).
+            // For STDIN and compressed files, they can not be read again later.
+            if !reader.content_type.is_binary() {
+                reader.peek_first_lines(MODELINE_LINES)?;
+            }
+        }
+
+        // Ranges relative to the end of the file need the number of lines up front. The whole
+        // input is still passed to the printer, which highlights it from the start. Binary content
+        // is not printed in interactive mode, so there is no need to buffer it.
+        let end_anchored = self.config.line_ranges.is_end_anchored()
+            || self.config.highlighted_lines.is_end_anchored();
+        if end_anchored && (!reader.content_type.is_binary() || self.config.loop_through) {
+            reader.buffer_all()?;
+        }
+
+        if self.config.loop_through {
+            let mut printer = SimplePrinter::new();
+            self.print_file(reader, &mut printer, writer, input_file, changed_lines)
+        } else {
+            let mut printer = InteractivePrinter::new(
+                &self.config,
+                &self.assets,
+                input_file,
+                file_name,
+                &reader,
+                line_changes,
+            );
+            self.print_file(reader, &mut printer, writer, input_file, changed_lines)
+        }
+    }
+
+    /// The lines around the Git modifications of a file, if only those should be printed. Input
+    /// which is not part of a repository (like STDIN) has no modifications to print.
+    fn changed_lines(&self, line_changes: Option<&LineChanges>) -> Option<LineRanges> {
+        if !self.config.only_changed_lines {
+            return None;
+        }
+
+        let line_changes = match line_changes {
+            Some(line_changes) => line_changes,
+            None => return Some(LineRanges::none()),
+        };
+        let context = self.config.diff_context;
+        Some(LineRanges::from(
+            line_changes
+                .keys()
+                .map(|&line_number| LineRange::around(line_number as usize, context))
+                .collect::<Vec<_>>(),
+        ))
+    }
+
     fn print_file<'a, P: Printer>(
         &self,
+        mut reader: InputFileReader,
         printer: &mut P,
         writer: &mut Write,
-        filename: InputFile<'a>,
+        input_file: InputFile<'a>,
+        changed_lines: Option<LineRanges>,
     ) -> Result<()> {
-        let stdin = io::stdin();
-        {
-            let reader: Box<BufRead> = match filename {
-                InputFile::StdIn => Box::new(stdin.lock()),
-                InputFile::Ordinary(filename) => Box::new(BufReader::new(File::open(filename)?)),
-                InputFile::ThemePreviewFile => Box::new(THEME_PREVIEW_FILE),
+        printer.print_header(writer, input_file)?;
+
+        // Binary content is only passed through in `cat` mode, it would garble the terminal
+        // otherwise.
+        if !reader.content_type.is_binary() || self.config.loop_through {
+            let mut line_ranges = match reader.num_lines {
+                Some(num_lines) => self.config.line_ranges.resolve(num_lines),
+                None => self.config.line_ranges.clone(),
             };
 
-            printer.print_header(writer, filename)?;
-            self.print_file_ranges(printer, writer, reader, &self.config.line_range)?;
-            printer.print_footer(writer)?;
+            if let Some(ref pattern) = self.config.pattern {
+                if self.config.only_matching_lines {
+                    let context = self.config.context;
+                    let matches: Vec<_> = reader
+                        .matching_lines(pattern)?
+                        .into_iter()
+                        .map(|line_number| LineRange::around(line_number, context))
+                        .collect();
+                    line_ranges = line_ranges.intersect(&LineRanges::from(matches));
+                }
+            }
+
+            if let Some(changed_lines) = changed_lines {
+                line_ranges = line_ranges.intersect(&changed_lines);
+            }
+
+            self.print_file_ranges(printer, writer, reader, &line_ranges)?;
         }
+
+        printer.print_footer(writer)?;
+
         Ok(())
     }
 
-    fn print_file_ranges<'a, P: Printer>(
+    fn print_file_ranges<P: Printer>(
         &self,
         printer: &mut P,
         writer: &mut Write,
-        mut reader: Box<BufRead + 'a>,
-        line_ranges: &Option<LineRange>,
+        mut reader: InputFileReader,
+        line_ranges: &LineRanges,
     ) -> Result<()> {
         let mut line_buffer = Vec::new();
 
         let mut line_number: usize = 1;
 
-        while reader.read_until(b'\n', &mut line_buffer)? > 0 {
-            {
-                match line_ranges {
-                    &Some(ref range) => {
-                        if line_number < range.lower {
-                            // Call the printer in case we need to call the syntax highlighter
-                            // for this line. However, set `out_of_range` to `true`.
-                            printer.print_line(true, writer, line_number, &line_buffer)?;
-                        } else if line_number > range.upper {
-                            // no more lines in range, exit early
-                            break;
-                        } else {
-                            printer.print_line(false, writer, line_number, &line_buffer)?;
-                        }
-                    }
-                    &None => {
-                        printer.print_line(false, writer, line_number, &line_buffer)?;
+        let mut first_range: bool = true;
+        let mut mid_range: bool = false;
+
+        while reader.read_line(&mut line_buffer)? {
+            match line_ranges.check(line_number) {
+                RangeCheckResult::OutsideRange => {
+                    // Call the printer in case we need to call the syntax highlighter
+                    // for this line. However, set `out_of_range` to `true`.
+                    printer.print_line(true, writer, line_number, &line_buffer)?;
+                    mid_range = false;
+                }
+
+                RangeCheckResult::InRange => {
+                    // Separate discontiguous chunks of the file.
+                    if first_range {
+                        first_range = false;
+                        mid_range = true;
+                    } else if !mid_range {
+                        mid_range = true;
+                        printer.print_snip(writer)?;
                     }
+
+                    printer.print_line(false, writer, line_number, &line_buffer)?;
                 }
 
-                line_number += 1;
+                RangeCheckResult::AfterLastRange => {
+                    // no more lines in range, exit early
+                    break;
+                }
             }
+
+            line_number += 1;
             line_buffer.clear();
         }
         Ok(())
@@ -0,0 +1,111 @@
+use std::io::{self, BufRead, Read};
+
+use encoding_rs::Decoder;
+
+const BUFFER_CAPACITY: usize = 8 * 1024;
+
+/// Transcodes the wrapped reader into UTF-8 on the fly, such that lines can be split at `\n`
+/// bytes regardless of the original encoding.
+pub struct DecodingReader<R> {
+    inner: R,
+    decoder: Decoder,
+    buffer: Vec<u8>,
+    pos: usize,
+    eof: bool,
+}
+
+impl<R: BufRead> DecodingReader<R> {
+    pub fn new(inner: R, decoder: Decoder) -> Self {
+        DecodingReader {
+            inner,
+            decoder,
+            buffer: Vec::with_capacity(BUFFER_CAPACITY),
+            pos: 0,
+            eof: false,
+        }
+    }
+}
+
+impl<R: BufRead> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = {
+            let available = self.fill_buf()?;
+            let size = available.len().min(buf.len());
+            buf[..size].copy_from_slice(&available[..size]);
+            size
+        };
+        self.consume(size);
+        Ok(size)
+    }
+}
+
+impl<R: BufRead> BufRead for DecodingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        // Decoding might consume input without producing any output (e.g. for a partial code
+        // unit), so keep going until there is something to return or the input is exhausted.
+        while self.pos >= self.buffer.len() && !self.eof {
+            self.buffer.clear();
+            self.buffer.resize(BUFFER_CAPACITY, 0);
+            self.pos = 0;
+
+            let (read, written, last) = {
+                let input = self.inner.fill_buf()?;
+                let last = input.is_empty();
+                let (_, read, written, _) =
+                    self.decoder.decode_to_utf8(input, &mut self.buffer, last);
+                (read, written, last)
+            };
+
+            self.inner.consume(read);
+            self.buffer.truncate(written);
+            self.eof = last;
+        }
+
+        Ok(&self.buffer[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.buffer.len());
+    }
+}
+
+#[cfg(test)]
+fn decode_all(input: &[u8], decoder: Decoder) -> String {
+    let mut output = String::new();
+    DecodingReader::new(input, decoder)
+        .read_to_string(&mut output)
+        .unwrap();
+    output
+}
+
+#[test]
+fn test_decode_utf16le_with_bom() {
+    use encoding_rs::UTF_16LE;
+
+    let input = b"\xff\xfeh\x00i\x00\n\x00\xe4\x00\n\x00";
+    let output = decode_all(input, UTF_16LE.new_decoder_with_bom_removal());
+    assert_eq!("hi\n\u{e4}\n", output);
+}
+
+#[test]
+fn test_decode_utf16be_lines() {
+    use encoding_rs::UTF_16BE;
+
+    let input = b"\x00a\x00\n\x00b\x00\n";
+    let mut reader = DecodingReader::new(&input[..], UTF_16BE.new_decoder_without_bom_handling());
+
+    let mut line = vec![];
+    reader.read_until(b'\n', &mut line).unwrap();
+    assert_eq!(b"a\n", &line[..]);
+}
+
+#[test]
+fn test_decode_latin1() {
+    use encoding_rs::WINDOWS_1252;
+
+    let output = decode_all(
+        b"caf\xe9\n",
+        WINDOWS_1252.new_decoder_without_bom_handling(),
+    );
+    assert_eq!("caf\u{e9}\n", output);
+}
@@ -1,5 +1,7 @@
+use ansi_term::Colour::Fixed;
 use ansi_term::Style;
-use diff::LineChange;
+use blame::format_age;
+use diff::{ChangeState, LineChange};
 use printer::{Colors, InteractivePrinter};
 
 #[derive(Clone)]
@@ -74,6 +76,10 @@ pub struct LineChangesDecoration {
     cached_removed_above: DecorationText,
     cached_removed_below: DecorationText,
     cached_modified: DecorationText,
+    cached_staged_added: DecorationText,
+    cached_staged_removed_above: DecorationText,
+    cached_staged_removed_below: DecorationText,
+    cached_staged_modified: DecorationText,
 }
 
 impl LineChangesDecoration {
@@ -92,6 +98,10 @@ impl LineChangesDecoration {
             cached_removed_above: Self::generate_cached(colors.git_removed, "‾"),
             cached_removed_below: Self::generate_cached(colors.git_removed, "_"),
             cached_modified: Self::generate_cached(colors.git_modified, "~"),
+            cached_staged_added: Self::generate_cached(colors.git_staged, "✚"),
+            cached_staged_removed_above: Self::generate_cached(colors.git_staged, "‾"),
+            cached_staged_removed_below: Self::generate_cached(colors.git_staged, "_"),
+            cached_staged_modified: Self::generate_cached(colors.git_staged, "≈"),
         }
     }
 }
@@ -106,11 +116,19 @@ impl Decoration for LineChangesDecoration {
         if !continuation {
             if let Some(ref changes) = printer.line_changes {
                 return match changes.get(&(line_number as u32)) {
-                    Some(&LineChange::Added) => self.cached_added.clone(),
-                    Some(&LineChange::RemovedAbove) => self.cached_removed_above.clone(),
-                    Some(&LineChange::RemovedBelow) => self.cached_removed_below.clone(),
-                    Some(&LineChange::Modified) => self.cached_modified.clone(),
-                    _ => self.cached_none.clone(),
+                    Some(&(change, ChangeState::Unstaged)) => match change {
+                        LineChange::Added => self.cached_added.clone(),
+                        LineChange::RemovedAbove => self.cached_removed_above.clone(),
+                        LineChange::RemovedBelow => self.cached_removed_below.clone(),
+                        LineChange::Modified => self.cached_modified.clone(),
+                    },
+                    Some(&(change, ChangeState::Staged)) => match change {
+                        LineChange::Added => self.cached_staged_added.clone(),
+                        LineChange::RemovedAbove => self.cached_staged_removed_above.clone(),
+                        LineChange::RemovedBelow => self.cached_staged_removed_below.clone(),
+                        LineChange::Modified => self.cached_staged_modified.clone(),
+                    },
+                    None => self.cached_none.clone(),
                 };
             }
         }
@@ -123,6 +141,81 @@ impl Decoration for LineChangesDecoration {
     }
 }
 
+/// The colors of the blame heatmap, from recent to old commits
+const BLAME_HEATMAP_COLORS: &[u8] = &[196, 202, 208, 214, 143, 109, 67, 60];
+
+pub struct BlameDecoration {
+    color: Style,
+    heatmap: bool,
+    cached_none: DecorationText,
+    cached_uncommitted: DecorationText,
+}
+
+impl BlameDecoration {
+    /// The abbreviated commit id, the initials of the author and the age of the commit
+    const WIDTH: usize = 7 + 1 + 3 + 1 + 9;
+
+    pub fn new(colors: &Colors, heatmap: bool) -> Self {
+        BlameDecoration {
+            color: colors.line_number,
+            heatmap,
+            cached_none: DecorationText {
+                text: " ".repeat(Self::WIDTH),
+                width: Self::WIDTH,
+            },
+            cached_uncommitted: DecorationText {
+                text: colors
+                    .line_number
+                    .paint(format!("{:width$}", "Not committed yet", width = Self::WIDTH))
+                    .to_string(),
+                width: Self::WIDTH,
+            },
+        }
+    }
+}
+
+impl Decoration for BlameDecoration {
+    fn generate(
+        &self,
+        line_number: usize,
+        continuation: bool,
+        printer: &InteractivePrinter,
+    ) -> DecorationText {
+        let blame = match printer.blame {
+            Some(ref blame) if !continuation => blame,
+            _ => return self.cached_none.clone(),
+        };
+
+        let commit = match blame.get(line_number as u32) {
+            Some(commit) => commit,
+            None => return self.cached_uncommitted.clone(),
+        };
+
+        let color = if self.heatmap {
+            let last = BLAME_HEATMAP_COLORS.len() - 1;
+            let index = (blame.relative_age(commit) * last as f64).round() as usize;
+            Fixed(BLAME_HEATMAP_COLORS[index.min(last)]).normal()
+        } else {
+            self.color
+        };
+
+        let text = format!(
+            "{} {:3} {:>9}",
+            commit.short_id,
+            commit.initials,
+            format_age(blame.now - commit.time)
+        );
+        DecorationText {
+            text: color.paint(text).to_string(),
+            width: Self::WIDTH,
+        }
+    }
+
+    fn width(&self) -> usize {
+        Self::WIDTH
+    }
+}
+
 pub struct GridBorderDecoration {
     cached: DecorationText,
 }
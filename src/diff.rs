@@ -1,9 +1,9 @@
-use git2::{DiffOptions, IntoCString, Repository};
+use git2::{Diff, DiffOptions, IntoCString, Repository};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum LineChange {
     Added,
     RemovedAbove,
@@ -11,28 +11,86 @@ pub enum LineChange {
     Modified,
 }
 
-pub type LineChanges = HashMap<u32, LineChange>;
+/// Whether a change has already been added to the index (or committed since the base revision)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ChangeState {
+    Staged,
+    Unstaged,
+}
 
-pub fn get_git_diff(filename: &str) -> Option<LineChanges> {
-    let repo = Repository::discover(&filename).ok()?;
-    let path_absolute = fs::canonicalize(&filename).ok()?;
-    let path_relative_to_repo = path_absolute.strip_prefix(repo.workdir()?).ok()?;
+pub type LineChanges = HashMap<u32, (LineChange, ChangeState)>;
 
-    let mut diff_options = DiffOptions::new();
-    let pathspec = path_relative_to_repo.into_c_string().ok()?;
-    diff_options.pathspec(pathspec);
-    diff_options.context_lines(0);
+/// The lines of a file in `HEAD` (or in a base revision) which are no longer part of the file in
+/// the working tree.
+pub struct RemovedLines {
+    /// The contents of the file in the base revision
+    pub old_content: Vec<u8>,
+
+    /// The removed lines, by the line of the new file in front of which they were removed. Lines
+    /// removed at the end of the file are stored under the line after the last one.
+    pub hunks: HashMap<u32, RemovedHunk>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RemovedHunk {
+    /// The first removed line of the old file
+    pub first_line: u32,
+
+    /// The number of removed lines
+    pub num_lines: u32,
+
+    /// Whether the lines have been replaced by new lines (or only been deleted)
+    pub modified: bool,
+}
+
+/// The changes of the file in the working tree. Changes compared to the index are unstaged, all
+/// other changes compared to `HEAD` (or to the revision `base`, if given) are staged.
+pub fn get_git_diff(filename: &str, base: Option<&str>) -> Option<LineChanges> {
+    let (repo, path_relative_to_repo) = open_repository(filename)?;
+    let path_relative_to_repo = path_relative_to_repo.as_path();
+    let mut diff_options = single_file_diff_options(path_relative_to_repo)?;
+
+    // An explicitly given base has to exist, but there is no `HEAD` before the first commit.
+    let base_tree = match repo.revparse_single(base.unwrap_or("HEAD")) {
+        Ok(object) => Some(object.peel_to_tree().ok()?),
+        Err(_) if base.is_none() => None,
+        Err(_) => return None,
+    };
+
+    let mut line_changes: LineChanges = HashMap::new();
+
+    if let Some(tree) = base_tree {
+        let diff = repo
+            .diff_tree_to_workdir(Some(&tree), Some(&mut diff_options))
+            .ok()?;
+        mark_changes(
+            &diff,
+            path_relative_to_repo,
+            ChangeState::Staged,
+            &mut line_changes,
+        );
+    }
 
+    // Unstaged changes replace the staged ones on the same lines.
     let diff = repo
         .diff_index_to_workdir(None, Some(&mut diff_options))
         .ok()?;
+    mark_changes(
+        &diff,
+        path_relative_to_repo,
+        ChangeState::Unstaged,
+        &mut line_changes,
+    );
 
-    let mut line_changes: LineChanges = HashMap::new();
+    Some(line_changes)
+}
 
+/// Mark all lines of the file at `path` that are changed in `diff` with the given state.
+fn mark_changes(diff: &Diff, path: &Path, state: ChangeState, line_changes: &mut LineChanges) {
     let mark_section =
         |line_changes: &mut LineChanges, start: u32, end: i32, change: LineChange| {
             for line in start..(end + 1) as u32 {
-                line_changes.insert(line, change);
+                line_changes.insert(line, (change, state));
             }
         };
 
@@ -40,9 +98,9 @@ pub fn get_git_diff(filename: &str) -> Option<LineChanges> {
         &mut |_, _| true,
         None,
         Some(&mut |delta, hunk| {
-            let path = delta.new_file().path().unwrap_or_else(|| Path::new(""));
+            let delta_path = delta.new_file().path().unwrap_or_else(|| Path::new(""));
 
-            if path_relative_to_repo != path {
+            if path != delta_path {
                 return false;
             }
 
@@ -52,26 +110,108 @@ pub fn get_git_diff(filename: &str) -> Option<LineChanges> {
             let new_end = (new_start + new_lines) as i32 - 1;
 
             if old_lines == 0 && new_lines > 0 {
-                mark_section(&mut line_changes, new_start, new_end, LineChange::Added);
+                mark_section(line_changes, new_start, new_end, LineChange::Added);
             } else if new_lines == 0 && old_lines > 0 {
                 if new_start == 0 {
-                    mark_section(&mut line_changes, 1, 1, LineChange::RemovedAbove);
+                    mark_section(line_changes, 1, 1, LineChange::RemovedAbove);
                 } else {
                     mark_section(
-                        &mut line_changes,
+                        line_changes,
                         new_start,
                         new_start as i32,
                         LineChange::RemovedBelow,
                     );
                 }
             } else {
-                mark_section(&mut line_changes, new_start, new_end, LineChange::Modified);
+                mark_section(line_changes, new_start, new_end, LineChange::Modified);
             }
 
             true
         }),
         None,
     );
+}
 
-    Some(line_changes)
+/// The lines that have been removed from the file in the working tree, compared to `HEAD` (or to
+/// the revision `base`, if given). This is `None` if the file is not part of that revision.
+pub fn get_removed_lines(filename: &str, base: Option<&str>) -> Option<RemovedLines> {
+    let (repo, path_relative_to_repo) = open_repository(filename)?;
+    let path_relative_to_repo = path_relative_to_repo.as_path();
+    let mut diff_options = single_file_diff_options(path_relative_to_repo)?;
+
+    let tree = repo
+        .revparse_single(base.unwrap_or("HEAD"))
+        .ok()?
+        .peel_to_tree()
+        .ok()?;
+    let old_content = tree
+        .get_path(path_relative_to_repo)
+        .ok()?
+        .to_object(&repo)
+        .ok()?
+        .peel_to_blob()
+        .ok()?
+        .content()
+        .to_vec();
+
+    let diff = repo
+        .diff_tree_to_workdir(Some(&tree), Some(&mut diff_options))
+        .ok()?;
+
+    let mut hunks = HashMap::new();
+    let _ = diff.foreach(
+        &mut |_, _| true,
+        None,
+        Some(&mut |delta, hunk| {
+            let delta_path = delta.new_file().path().unwrap_or_else(|| Path::new(""));
+
+            if path_relative_to_repo != delta_path {
+                return false;
+            }
+
+            if hunk.old_lines() > 0 {
+                let modified = hunk.new_lines() > 0;
+
+                // For pure deletions, `new_start` is the line after which the lines were removed.
+                let position = if modified {
+                    hunk.new_start()
+                } else {
+                    hunk.new_start() + 1
+                };
+
+                hunks.insert(
+                    position,
+                    RemovedHunk {
+                        first_line: hunk.old_start(),
+                        num_lines: hunk.old_lines(),
+                        modified,
+                    },
+                );
+            }
+
+            true
+        }),
+        None,
+    );
+
+    Some(RemovedLines { old_content, hunks })
+}
+
+/// The repository containing `filename` and the path of the file relative to its working
+/// directory.
+pub fn open_repository(filename: &str) -> Option<(Repository, PathBuf)> {
+    let repo = Repository::discover(&filename).ok()?;
+    let path_absolute = fs::canonicalize(&filename).ok()?;
+    let path_relative_to_repo = path_absolute.strip_prefix(repo.workdir()?).ok()?.to_owned();
+
+    Some((repo, path_relative_to_repo))
+}
+
+/// Options for a diff of the single file at `path`, without context lines.
+pub fn single_file_diff_options(path: &Path) -> Option<DiffOptions> {
+    let mut diff_options = DiffOptions::new();
+    diff_options.pathspec(path.into_c_string().ok()?);
+    diff_options.context_lines(0);
+
+    Some(diff_options)
 }
@@ -0,0 +1,27 @@
+use std::io;
+use std::process;
+
+error_chain! {
+    foreign_links {
+        Clap(::clap::Error);
+        Io(::std::io::Error);
+        SyntectError(::syntect::LoadingError);
+        ParseIntError(::std::num::ParseIntError);
+        GlobsetError(::globset::Error);
+        WalkError(::ignore::Error);
+        RegexError(::regex::Error);
+        TomlError(::toml::de::Error);
+    }
+}
+
+pub fn handle_error(error: &Error) {
+    match error {
+        &Error(ErrorKind::Io(ref io_error), _) if io_error.kind() == io::ErrorKind::BrokenPipe => {
+            process::exit(0);
+        }
+        _ => {
+            use ansi_term::Colour::Red;
+            eprintln!("{}: {}", Red.paint("[bat error]"), error);
+        }
+    };
+}
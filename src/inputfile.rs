@@ -0,0 +1,317 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+use std::mem;
+
+use content_inspector::{self, ContentType};
+
+use encoding_rs::Encoding;
+
+use regex::Regex;
+
+use compression::Compression;
+use decoding::DecodingReader;
+use errors::*;
+use revision;
+
+const THEME_PREVIEW_FILE: &[u8] = include_bytes!("../assets/theme_preview.rs");
+
+pub struct InputFileReader<'a> {
+    inner: Box<BufRead + 'a>,
+    pub content_type: ContentType,
+
+    /// The compression format of the input, if it has been decompressed
+    pub compression: Option<Compression>,
+
+    /// The encoding the input has been transcoded from, if any
+    pub encoding: Option<&'static Encoding>,
+
+    /// The number of lines, if the input has been buffered
+    pub num_lines: Option<usize>,
+
+    /// The first lines of the input, if they have been peeked at
+    pub first_lines: Vec<String>,
+}
+
+impl<'a> InputFileReader<'a> {
+    fn new<R: BufRead + 'a>(mut reader: R) -> Result<InputFileReader<'a>> {
+        // Inspect the first block of the input without consuming it.
+        let content_type = content_inspector::inspect(reader.fill_buf()?);
+
+        Ok(InputFileReader {
+            inner: Box::new(reader),
+            content_type,
+            compression: None,
+            encoding: None,
+            num_lines: None,
+            first_lines: vec![],
+        })
+    }
+
+    /// A reader for content which is already in memory, like a file from a Git revision
+    pub fn from_bytes(content: Vec<u8>) -> Result<InputFileReader<'a>> {
+        InputFileReader::new(Cursor::new(content))
+    }
+
+    pub fn read_line(&mut self, buf: &mut Vec<u8>) -> io::Result<bool> {
+        self.inner.read_until(b'\n', buf).map(|size| size > 0)
+    }
+
+    /// Decompress the input on the fly if it starts with the magic bytes of a known compression
+    /// format. The content type is determined again for the decompressed data.
+    pub fn decompress(&mut self) -> io::Result<()> {
+        if let Some(compression) = Compression::detect(self.inner.fill_buf()?) {
+            let inner = mem::replace(&mut self.inner, Box::new(io::empty()));
+            self.inner = compression.decoder(inner)?;
+            self.compression = Some(compression);
+            self.content_type = content_inspector::inspect(self.inner.fill_buf()?);
+        }
+
+        Ok(())
+    }
+
+    /// Transcode the input to UTF-8 if it starts with a byte order mark or if a `fallback`
+    /// encoding is given. A byte order mark always takes precedence.
+    pub fn decode(&mut self, fallback: Option<&'static Encoding>) -> io::Result<()> {
+        let decoder = match Encoding::for_bom(self.inner.fill_buf()?) {
+            Some((encoding, _)) => Some((encoding, encoding.new_decoder_with_bom_removal())),
+            // Binary content is passed through unchanged, unless a byte order mark says otherwise.
+            None if self.content_type.is_binary() => None,
+            None => {
+                fallback.map(|encoding| (encoding, encoding.new_decoder_without_bom_handling()))
+            }
+        };
+
+        if let Some((encoding, decoder)) = decoder {
+            let inner = mem::replace(&mut self.inner, Box::new(io::empty()));
+            self.inner = Box::new(DecodingReader::new(inner, decoder));
+            self.encoding = Some(encoding);
+
+            // From here on, the reader only yields UTF-8.
+            self.content_type = ContentType::UTF_8;
+        }
+
+        Ok(())
+    }
+
+    /// Read up to `count` lines from the start of the input (e.g. to detect the syntax from a
+    /// shebang or a modeline) without consuming them.
+    pub fn peek_first_lines(&mut self, count: usize) -> io::Result<()> {
+        let mut buffer = vec![];
+        for _ in 0..count {
+            let start = buffer.len();
+            if self.inner.read_until(b'\n', &mut buffer)? == 0 {
+                break;
+            }
+            let line = String::from_utf8_lossy(&buffer[start..]).into_owned();
+            self.first_lines.push(line);
+        }
+
+        let inner = mem::replace(&mut self.inner, Box::new(io::empty()));
+        self.inner = Box::new(Cursor::new(buffer).chain(inner));
+
+        Ok(())
+    }
+
+    /// Read the remaining input into memory, such that the number of lines is known before
+    /// anything is printed. Returns the number of lines.
+    pub fn buffer_all(&mut self) -> io::Result<usize> {
+        self.buffer_lines(|_, _| {})
+    }
+
+    /// Read the remaining input into memory (like `buffer_all`) and return the numbers of all
+    /// lines that match the given `pattern`.
+    pub fn matching_lines(&mut self, pattern: &Regex) -> io::Result<Vec<usize>> {
+        let mut matching_lines = vec![];
+        self.buffer_lines(|line_number, line| {
+            if pattern.is_match(&String::from_utf8_lossy(line)) {
+                matching_lines.push(line_number);
+            }
+        })?;
+
+        Ok(matching_lines)
+    }
+
+    /// Read the remaining input into memory and call `inspect` with every line (without its
+    /// line ending) and its number. Returns the number of lines.
+    fn buffer_lines<F: FnMut(usize, &[u8])>(&mut self, mut inspect: F) -> io::Result<usize> {
+        let mut buffer = Vec::new();
+        self.inner.read_to_end(&mut buffer)?;
+
+        let mut num_lines = 0;
+        {
+            let mut lines = buffer.split(|&b| b == b'\n').peekable();
+            while let Some(line) = lines.next() {
+                // There is no line after the final line ending.
+                if line.is_empty() && lines.peek().is_none() {
+                    break;
+                }
+
+                num_lines += 1;
+                let line = if line.last() == Some(&b'\r') {
+                    &line[..line.len() - 1]
+                } else {
+                    line
+                };
+                inspect(num_lines, line);
+            }
+        }
+
+        self.inner = Box::new(Cursor::new(buffer));
+        self.num_lines = Some(num_lines);
+
+        Ok(num_lines)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputFile<'a> {
+    StdIn,
+    Ordinary(&'a str),
+
+    /// A file in a Git revision: the revision (like 'HEAD~2') and the path of the file, relative
+    /// to the current directory
+    Revision(&'a str, &'a str),
+
+    ThemePreviewFile,
+}
+
+impl<'a> InputFile<'a> {
+    pub fn get_reader(&self, stdin: &'a io::Stdin) -> Result<InputFileReader<'a>> {
+        match *self {
+            InputFile::StdIn => InputFileReader::new(stdin.lock()),
+            InputFile::Ordinary(filename) => {
+                let file = File::open(filename)?;
+
+                if file.metadata()?.is_dir() {
+                    return Err(format!(
+                        "'{}' is a directory. Use '--recursive' to print the files inside of it.",
+                        filename
+                    ).into());
+                }
+
+                InputFileReader::new(BufReader::new(file))
+            }
+            InputFile::Revision(revision, path) => {
+                InputFileReader::from_bytes(revision::read_file(revision, path)?)
+            }
+            InputFile::ThemePreviewFile => InputFileReader::new(THEME_PREVIEW_FILE),
+        }
+    }
+}
+
+#[test]
+fn test_binary_detection() {
+    let reader = InputFileReader::new(&b"\x7fELF\x02\x01\x01\x00\x00\x00"[..]).unwrap();
+    assert!(reader.content_type.is_binary());
+
+    let reader = InputFileReader::new(&b"fn main() {}\n"[..]).unwrap();
+    assert!(!reader.content_type.is_binary());
+}
+
+#[test]
+fn test_read_line_after_inspection() {
+    let mut reader = InputFileReader::new(&b"first\nsecond\n"[..]).unwrap();
+
+    let mut buffer = vec![];
+    assert!(reader.read_line(&mut buffer).unwrap());
+    assert_eq!(b"first\n", &buffer[..]);
+
+    buffer.clear();
+    assert!(reader.read_line(&mut buffer).unwrap());
+    assert_eq!(b"second\n", &buffer[..]);
+
+    buffer.clear();
+    assert!(!reader.read_line(&mut buffer).unwrap());
+}
+
+#[test]
+fn test_peek_first_lines() {
+    let mut reader = InputFileReader::new(&b"#!/bin/sh\necho\nexit\n"[..]).unwrap();
+    reader.peek_first_lines(2).unwrap();
+    assert_eq!(vec!["#!/bin/sh\n", "echo\n"], reader.first_lines);
+
+    // Nothing is lost, the first lines are still part of the input.
+    assert_eq!(3, reader.buffer_all().unwrap());
+
+    let mut buffer = vec![];
+    assert!(reader.read_line(&mut buffer).unwrap());
+    assert_eq!(b"#!/bin/sh\n", &buffer[..]);
+}
+
+#[test]
+fn test_decode_bom() {
+    use encoding_rs::{UTF_16LE, WINDOWS_1252};
+
+    let mut reader = InputFileReader::new(&b"\xff\xfea\x00\n\x00"[..]).unwrap();
+    assert_eq!(ContentType::UTF_16LE, reader.content_type);
+
+    // The byte order mark wins over the explicitly specified encoding.
+    reader.decode(Some(WINDOWS_1252)).unwrap();
+    assert_eq!(Some(UTF_16LE), reader.encoding);
+
+    let mut buffer = vec![];
+    assert!(reader.read_line(&mut buffer).unwrap());
+    assert_eq!(b"a\n", &buffer[..]);
+}
+
+#[test]
+fn test_decode_without_bom() {
+    let mut reader = InputFileReader::new(&b"plain\n"[..]).unwrap();
+    reader.decode(None).unwrap();
+    assert_eq!(None, reader.encoding);
+}
+
+#[test]
+fn test_decode_binary() {
+    use encoding_rs::WINDOWS_1252;
+
+    let mut reader = InputFileReader::new(&b"\x7fELF\x02\x01\x01\x00\x00\x00"[..]).unwrap();
+    reader.decode(Some(WINDOWS_1252)).unwrap();
+    assert_eq!(None, reader.encoding);
+    assert!(reader.content_type.is_binary());
+}
+
+#[test]
+fn test_decompress() {
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), ::flate2::Compression::default());
+    encoder.write_all(b"first\nsecond\n").unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let mut reader = InputFileReader::new(&compressed[..]).unwrap();
+    assert!(reader.content_type.is_binary());
+
+    reader.decompress().unwrap();
+    assert_eq!(Some(Compression::Gzip), reader.compression);
+    assert!(!reader.content_type.is_binary());
+
+    let mut buffer = vec![];
+    assert!(reader.read_line(&mut buffer).unwrap());
+    assert_eq!(b"first\n", &buffer[..]);
+}
+
+#[test]
+fn test_matching_lines() {
+    let mut reader = InputFileReader::new(&b"foo\nbar\r\nfoobar\nbaz"[..]).unwrap();
+    let pattern = Regex::new("^(foo|bar)$").unwrap();
+    assert_eq!(vec![1, 2], reader.matching_lines(&pattern).unwrap());
+    assert_eq!(Some(4), reader.num_lines);
+
+    // The input can still be read from the start.
+    let mut buffer = vec![];
+    assert!(reader.read_line(&mut buffer).unwrap());
+    assert_eq!(b"foo\n", &buffer[..]);
+}
+
+#[test]
+fn test_buffer_all() {
+    let mut reader = InputFileReader::new(&b"first\nsecond\nthird"[..]).unwrap();
+    assert_eq!(3, reader.buffer_all().unwrap());
+    assert_eq!(Some(3), reader.num_lines);
+
+    let mut buffer = vec![];
+    assert!(reader.read_line(&mut buffer).unwrap());
+    assert_eq!(b"first\n", &buffer[..]);
+}
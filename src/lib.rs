@@ -0,0 +1,76 @@
+//! `bat` as a library: pretty-print files with syntax highlighting and Git integration into any
+//! `Write` sink.
+//!
+//! ```no_run
+//! use bat::PrettyPrinter;
+//!
+//! PrettyPrinter::new()
+//!     .input_file("src/main.rs")
+//!     .line_numbers(true)
+//!     .print()
+//!     .unwrap();
+//! ```
+
+// `error_chain!` can recurse deeply
+#![recursion_limit = "1024"]
+
+#[macro_use]
+extern crate error_chain;
+
+#[macro_use]
+extern crate clap;
+
+#[macro_use]
+extern crate lazy_static;
+
+#[macro_use]
+extern crate serde_derive;
+
+extern crate ansi_term;
+extern crate bzip2;
+extern crate console;
+extern crate content_inspector;
+extern crate directories;
+extern crate encoding_rs;
+extern crate flate2;
+extern crate git2;
+extern crate globset;
+extern crate ignore;
+extern crate regex;
+extern crate syntect;
+extern crate toml;
+extern crate unicode_segmentation;
+extern crate unicode_width;
+extern crate xz2;
+extern crate zstd;
+
+pub mod assets;
+mod blame;
+pub mod compression;
+pub mod config;
+pub mod controller;
+mod decoding;
+mod decorations;
+mod diff;
+pub mod errors;
+pub mod inputfile;
+pub mod line_range;
+mod modeline;
+mod output;
+mod preprocessor;
+pub mod pretty_printer;
+pub mod printer;
+pub mod project_config;
+mod revision;
+pub mod style;
+pub mod syntax_mapping;
+mod terminal;
+pub mod walk;
+mod wrap;
+
+pub use assets::HighlightingAssets;
+pub use config::{Config, PagingMode};
+pub use controller::Controller;
+pub use inputfile::InputFile;
+pub use pretty_printer::PrettyPrinter;
+pub use printer::{InteractivePrinter, Printer, SimplePrinter};
@@ -1,9 +1,15 @@
 use errors::*;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct LineRange {
     pub lower: usize,
     pub upper: usize,
+
+    /// Whether `lower` counts backwards from the end of the input (`-N`)
+    lower_from_end: bool,
+
+    /// Whether `upper` counts backwards from the end of the input (`-N`)
+    upper_from_end: bool,
 }
 
 impl LineRange {
@@ -15,31 +21,222 @@ impl LineRange {
         LineRange {
             lower: usize::min_value(),
             upper: usize::max_value(),
+            lower_from_end: false,
+            upper_from_end: false,
+        }
+    }
+
+    /// Line `line` with `context` lines on each side.
+    pub fn around(line: usize, context: usize) -> LineRange {
+        LineRange {
+            lower: line.saturating_sub(context),
+            upper: line.saturating_add(context),
+            ..LineRange::new()
         }
     }
 
     pub fn parse_range(range_raw: &str) -> Result<LineRange> {
         let mut new_range = LineRange::new();
 
-        if range_raw.bytes().nth(0).ok_or("Empty line range")? == b':' {
-            new_range.upper = range_raw[1..].parse()?;
-            return Ok(new_range);
-        } else if range_raw.bytes().last().ok_or("Empty line range")? == b':' {
-            new_range.lower = range_raw[..range_raw.len() - 1].parse()?;
-            return Ok(new_range);
+        if range_raw.is_empty() {
+            return Err("Empty line range".into());
+        }
+
+        // 'N::C' is line N with C lines of context on each side.
+        if let Some(pos) = range_raw.find("::") {
+            let line: usize = range_raw[..pos].parse()?;
+            let context: usize = range_raw[pos + 2..].parse()?;
+            return Ok(LineRange::around(line, context));
         }
 
         let line_numbers: Vec<&str> = range_raw.split(':').collect();
-        if line_numbers.len() == 2 {
-            new_range.lower = line_numbers[0].parse()?;
-            new_range.upper = line_numbers[1].parse()?;
-            return Ok(new_range);
+        match line_numbers.len() {
+            1 => {
+                let (line, from_end) = parse_bound(line_numbers[0])?;
+                new_range.lower = line;
+                new_range.upper = line;
+                new_range.lower_from_end = from_end;
+                new_range.upper_from_end = from_end;
+                Ok(new_range)
+            }
+            2 => {
+                let (lower_raw, upper_raw) = (line_numbers[0], line_numbers[1]);
+                if lower_raw.is_empty() && upper_raw.is_empty() {
+                    return Err("Empty line range".into());
+                }
+
+                if !lower_raw.is_empty() {
+                    let (lower, from_end) = parse_bound(lower_raw)?;
+                    new_range.lower = lower;
+                    new_range.lower_from_end = from_end;
+                }
+
+                if upper_raw.starts_with('+') {
+                    // 'N:+M' is line N and the M lines after it.
+                    if lower_raw.is_empty() || new_range.lower_from_end {
+                        return Err("'+M' requires an absolute start line".into());
+                    }
+                    let count: usize = upper_raw[1..].parse()?;
+                    new_range.upper = new_range.lower.saturating_add(count);
+                } else if !upper_raw.is_empty() {
+                    let (upper, from_end) = parse_bound(upper_raw)?;
+                    new_range.upper = upper;
+                    new_range.upper_from_end = from_end;
+                }
+
+                Ok(new_range)
+            }
+            _ => Err("expected single ':' character".into()),
+        }
+    }
+
+    /// Whether this range can only be resolved once the number of lines is known.
+    pub fn is_end_anchored(&self) -> bool {
+        self.lower_from_end || self.upper_from_end
+    }
+
+    /// Turn bounds relative to the end of the input into absolute line numbers.
+    pub fn resolve(&self, num_lines: usize) -> LineRange {
+        let from_end = |n: usize| (num_lines + 1).saturating_sub(n);
+
+        LineRange {
+            lower: if self.lower_from_end {
+                from_end(self.lower)
+            } else {
+                self.lower
+            },
+            upper: if self.upper_from_end {
+                from_end(self.upper)
+            } else {
+                self.upper
+            },
+            lower_from_end: false,
+            upper_from_end: false,
         }
+    }
+
+    pub fn is_inside(&self, line: usize) -> bool {
+        line >= self.lower && line <= self.upper
+    }
+}
 
-        Err("expected single ':' character".into())
+/// Parses a single line number, which counts backwards from the end if it starts with '-'.
+fn parse_bound(bound_raw: &str) -> Result<(usize, bool)> {
+    if bound_raw.starts_with('-') {
+        Ok((bound_raw[1..].parse()?, true))
+    } else if bound_raw.starts_with('+') {
+        Err("unexpected '+' character".into())
+    } else {
+        Ok((bound_raw.parse()?, false))
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RangeCheckResult {
+    /// Within one of the given ranges
+    InRange,
+
+    /// Before the first range or between two ranges
+    OutsideRange,
+
+    /// Outside of all ranges and after the last range
+    AfterLastRange,
+}
+
+/// A set of line ranges. Overlapping or adjacent ranges are merged, so that every line is part of
+/// at most one range and the ranges are sorted.
+#[derive(Clone)]
+pub struct LineRanges {
+    ranges: Vec<LineRange>,
+}
+
+impl From<Vec<LineRange>> for LineRanges {
+    fn from(ranges: Vec<LineRange>) -> LineRanges {
+        // Ranges relative to the end of the input are merged when they are resolved.
+        if ranges.iter().any(LineRange::is_end_anchored) {
+            return LineRanges { ranges };
+        }
+
+        LineRanges {
+            ranges: merge(ranges),
+        }
+    }
+}
+
+impl LineRanges {
+    pub fn all() -> LineRanges {
+        LineRanges::from(vec![LineRange::new()])
+    }
+
+    pub fn none() -> LineRanges {
+        LineRanges::from(vec![])
+    }
+
+    /// Whether the number of lines needs to be known before the ranges can be checked.
+    pub fn is_end_anchored(&self) -> bool {
+        self.ranges.iter().any(LineRange::is_end_anchored)
+    }
+
+    /// Turn all ranges relative to the end of the input into absolute ranges.
+    pub fn resolve(&self, num_lines: usize) -> LineRanges {
+        LineRanges::from(
+            self.ranges
+                .iter()
+                .map(|r| r.resolve(num_lines))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// The lines which are part of both `self` and `other`. Both have to be resolved.
+    pub fn intersect(&self, other: &LineRanges) -> LineRanges {
+        let mut ranges = vec![];
+        for a in &self.ranges {
+            for b in &other.ranges {
+                if a.lower.max(b.lower) <= a.upper.min(b.upper) {
+                    ranges.push(LineRange {
+                        lower: a.lower.max(b.lower),
+                        upper: a.upper.min(b.upper),
+                        ..LineRange::new()
+                    });
+                }
+            }
+        }
+
+        LineRanges::from(ranges)
+    }
+
+    pub fn ranges(&self) -> &[LineRange] {
+        &self.ranges
+    }
+
+    pub fn check(&self, line: usize) -> RangeCheckResult {
+        if self.ranges.iter().any(|r| r.is_inside(line)) {
+            RangeCheckResult::InRange
+        } else if self.ranges.iter().any(|r| line < r.lower) {
+            RangeCheckResult::OutsideRange
+        } else {
+            RangeCheckResult::AfterLastRange
+        }
+    }
+}
+
+fn merge(mut ranges: Vec<LineRange>) -> Vec<LineRange> {
+    ranges.sort_by_key(|range| range.lower);
+
+    let mut merged: Vec<LineRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(last) = merged.last_mut() {
+            if range.lower <= last.upper.saturating_add(1) {
+                last.upper = last.upper.max(range.upper);
+                continue;
+            }
+        }
+        merged.push(range);
+    }
+
+    merged
+}
+
 #[test]
 fn test_parse_full() {
     let range = LineRange::from("40:50").expect("Shouldn't fail on test!");
@@ -65,10 +262,136 @@ fn test_parse_partial_max() {
 fn test_parse_fail() {
     let range = LineRange::from("40:50:80");
     assert!(range.is_err());
-    let range = LineRange::from("40::80");
-    assert!(range.is_err());
     let range = LineRange::from(":40:");
     assert!(range.is_err());
-    let range = LineRange::from("40");
+    let range = LineRange::from("");
+    assert!(range.is_err());
+    let range = LineRange::from(":");
     assert!(range.is_err());
+    let range = LineRange::from("40:+");
+    assert!(range.is_err());
+    let range = LineRange::from(":+10");
+    assert!(range.is_err());
+    let range = LineRange::from("-20:+10");
+    assert!(range.is_err());
+    let range = LineRange::from("+40");
+    assert!(range.is_err());
+    let range = LineRange::from("40::");
+    assert!(range.is_err());
+}
+
+#[test]
+fn test_parse_single() {
+    let range = LineRange::from("40").expect("Shouldn't fail on test!");
+    assert_eq!(40, range.lower);
+    assert_eq!(40, range.upper);
+}
+
+#[test]
+fn test_parse_relative() {
+    let range = LineRange::from("40:+10").expect("Shouldn't fail on test!");
+    assert_eq!(40, range.lower);
+    assert_eq!(50, range.upper);
+}
+
+#[test]
+fn test_parse_context() {
+    let range = LineRange::from("40::5").expect("Shouldn't fail on test!");
+    assert_eq!(35, range.lower);
+    assert_eq!(45, range.upper);
+
+    let range = LineRange::from("3::5").expect("Shouldn't fail on test!");
+    assert_eq!(0, range.lower);
+    assert_eq!(8, range.upper);
+}
+
+#[test]
+fn test_parse_end_anchored() {
+    let range = LineRange::from("-20:").expect("Shouldn't fail on test!");
+    assert!(range.is_end_anchored());
+    let resolved = range.resolve(100);
+    assert_eq!(81, resolved.lower);
+    assert_eq!(usize::max_value(), resolved.upper);
+
+    let range = LineRange::from("10:-2").expect("Shouldn't fail on test!");
+    let resolved = range.resolve(100);
+    assert_eq!(10, resolved.lower);
+    assert_eq!(99, resolved.upper);
+
+    let range = LineRange::from("-200:").expect("Shouldn't fail on test!");
+    assert_eq!(0, range.resolve(100).lower);
+}
+
+#[cfg(test)]
+fn ranges(raw: &[&str]) -> LineRanges {
+    let ranges: Vec<_> = raw.iter().map(|r| LineRange::from(r).unwrap()).collect();
+    LineRanges::from(ranges)
+}
+
+#[test]
+fn test_ranges_merge_overlapping() {
+    let merged = ranges(&["200:230", "10:20", "15:25", "26:30"]);
+    let bounds: Vec<(usize, usize)> = merged
+        .ranges()
+        .iter()
+        .map(|r| (r.lower, r.upper))
+        .collect();
+    assert_eq!(vec![(10, 30), (200, 230)], bounds);
+}
+
+#[test]
+fn test_ranges_open_ended() {
+    let merged = ranges(&["40:", "10:50"]);
+    assert_eq!(1, merged.ranges().len());
+    assert_eq!(10, merged.ranges()[0].lower);
+    assert_eq!(usize::max_value(), merged.ranges()[0].upper);
+}
+
+#[test]
+fn test_ranges_check() {
+    let merged = ranges(&["10:20", "200:230"]);
+    assert_eq!(RangeCheckResult::OutsideRange, merged.check(5));
+    assert_eq!(RangeCheckResult::InRange, merged.check(10));
+    assert_eq!(RangeCheckResult::InRange, merged.check(20));
+    assert_eq!(RangeCheckResult::OutsideRange, merged.check(21));
+    assert_eq!(RangeCheckResult::InRange, merged.check(230));
+    assert_eq!(RangeCheckResult::AfterLastRange, merged.check(231));
+}
+
+#[test]
+fn test_ranges_all() {
+    let all = LineRanges::all();
+    assert_eq!(RangeCheckResult::InRange, all.check(1));
+    assert_eq!(RangeCheckResult::InRange, all.check(100000));
+}
+
+#[test]
+fn test_ranges_resolve() {
+    let anchored = ranges(&["-5:", "1:2", "94:95"]);
+    assert!(anchored.is_end_anchored());
+
+    let resolved = anchored.resolve(100);
+    assert!(!resolved.is_end_anchored());
+    let bounds: Vec<(usize, usize)> = resolved
+        .ranges()
+        .iter()
+        .map(|r| (r.lower, r.upper))
+        .collect();
+    assert_eq!(vec![(1, 2), (94, usize::max_value())], bounds);
+}
+
+#[test]
+fn test_ranges_none() {
+    assert_eq!(RangeCheckResult::AfterLastRange, LineRanges::none().check(1));
+}
+
+#[test]
+fn test_ranges_intersect() {
+    let intersection = ranges(&["1:10", "20:"]).intersect(&ranges(&["5::1", "9::2", "30"]));
+    let bounds: Vec<(usize, usize)> = intersection
+        .ranges()
+        .iter()
+        .map(|r| (r.lower, r.upper))
+        .collect();
+    assert_eq!(vec![(4, 10), (30, 30)], bounds);
 }
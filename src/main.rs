@@ -1,35 +1,18 @@
-// `error_chain!` can recurse deeply
-#![recursion_limit = "1024"]
-
-#[macro_use]
-extern crate error_chain;
-
 #[macro_use]
 extern crate clap;
 
-#[macro_use]
-extern crate lazy_static;
-
 extern crate ansi_term;
 extern crate atty;
+extern crate bat;
 extern crate console;
-extern crate directories;
-extern crate git2;
-extern crate syntect;
+extern crate encoding_rs;
+extern crate regex;
+extern crate shell_words;
 
 mod app;
-mod assets;
-mod controller;
-mod decorations;
-mod diff;
-mod line_range;
-mod output;
-mod printer;
-mod style;
-mod terminal;
+mod config_file;
 
 use std::collections::HashSet;
-use std::io;
 use std::io::stdout;
 use std::io::Write;
 use std::path::Path;
@@ -38,37 +21,14 @@ use std::process;
 use ansi_term::Colour::Green;
 use ansi_term::Style;
 
-use app::{App, Config, InputFile};
-use assets::{clear_assets, config_dir, HighlightingAssets};
-use controller::Controller;
-use style::{OutputComponent, OutputComponents};
-
-mod errors {
-    error_chain! {
-        foreign_links {
-            Clap(::clap::Error);
-            Io(::std::io::Error);
-            SyntectError(::syntect::LoadingError);
-            ParseIntError(::std::num::ParseIntError);
-        }
-    }
-
-    pub fn handle_error(error: &Error) {
-        match error {
-            &Error(ErrorKind::Io(ref io_error), _)
-                if io_error.kind() == super::io::ErrorKind::BrokenPipe =>
-            {
-                super::process::exit(0);
-            }
-            _ => {
-                use ansi_term::Colour::Red;
-                eprintln!("{}: {}", Red.paint("[bat error]"), error);
-            }
-        };
-    }
-}
-
-use errors::*;
+use app::App;
+use config_file::{config_file, generate_config_file};
+use bat::assets::{clear_assets, config_dir, HighlightingAssets};
+use bat::config::Config;
+use bat::controller::Controller;
+use bat::errors::*;
+use bat::inputfile::InputFile;
+use bat::style::{OutputComponent, OutputComponents};
 
 fn run_cache_subcommand(matches: &clap::ArgMatches) -> Result<()> {
     if matches.is_present("init") {
@@ -172,6 +132,19 @@ fn run() -> Result<bool> {
             run_cache_subcommand(cache_matches)?;
             Ok(true)
         }
+        _ if app.matches.is_present("config-file") => {
+            writeln!(stdout(), "{}", config_file().to_string_lossy())?;
+            Ok(true)
+        }
+        _ if app.matches.is_present("generate-config-file") => {
+            let path = generate_config_file()?;
+            writeln!(
+                stdout(),
+                "A default configuration file has been written to '{}'.",
+                path.to_string_lossy()
+            )?;
+            Ok(true)
+        }
         _ => {
             let config = app.config()?;
             let assets = HighlightingAssets::new();
@@ -0,0 +1,161 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use encoding_rs::Encoding;
+
+use regex::Regex;
+
+/// The number of lines at the start and at the end of the input which are searched for modelines
+/// (like the default of Vim's 'modelines' option)
+pub const MODELINE_LINES: usize = 5;
+
+/// Vim file types and Emacs modes which are neither the name nor an extension of a syntax
+const ALIASES: &[(&str, &str)] = &[
+    ("conf-unix", "ini"),
+    ("conf-windows", "ini"),
+    ("cperl", "perl"),
+    ("csharp", "cs"),
+    ("dosbatch", "bat"),
+    ("dosini", "ini"),
+    ("emacs-lisp", "lisp"),
+    ("eruby", "erb"),
+    ("fundamental", "txt"),
+    ("gfm", "md"),
+    ("gitcommit", "Git Commit"),
+    ("gitrebase", "Git Rebase Todo"),
+    ("jproperties", "properties"),
+    ("js2", "js"),
+    ("lhaskell", "lhs"),
+    ("lisp-interaction", "lisp"),
+    ("makefile-automake", "make"),
+    ("makefile-bsdmake", "make"),
+    ("makefile-gmake", "make"),
+    ("nxml", "xml"),
+    ("objc", "m"),
+    ("objcpp", "mm"),
+    ("octave", "matlab"),
+    ("plaintex", "TeX"),
+    ("scheme", "scm"),
+    ("shell-script", "sh"),
+    ("text", "txt"),
+    ("tuareg", "ml"),
+];
+
+lazy_static! {
+    static ref VIM_MODELINE: Regex =
+        Regex::new(r"(?:^|\s)(?:vim?(?:[<=>]?\d+)?|ex):\s*(?:set?\s+)?(.*)").unwrap();
+    static ref VIM_FILETYPE: Regex =
+        Regex::new(r"(?:^|[\s:])(?:ft|filetype|syn|syntax)=([\w+-]+)").unwrap();
+    static ref EMACS_MODELINE: Regex = Regex::new(r"-\*-\s*(.*?)\s*-\*-").unwrap();
+    static ref EMACS_MODE: Regex = Regex::new(r"(?i)(?:^|;)\s*mode\s*:\s*([\w+-]+)").unwrap();
+    static ref EMACS_MODE_ONLY: Regex = Regex::new(r"^[\w+-]+$").unwrap();
+}
+
+/// The file type set by a Vim (`vim: ft=yaml`) or Emacs (`-*- mode: ruby -*-`) modeline in the
+/// given line, if any.
+pub fn filetype(line: &str) -> Option<&str> {
+    if let Some(captures) = EMACS_MODELINE.captures(line) {
+        let variables = captures.get(1).unwrap().as_str();
+
+        // '-*- ruby -*-' is short for '-*- mode: ruby -*-'.
+        if EMACS_MODE_ONLY.is_match(variables) {
+            return Some(variables);
+        }
+        if let Some(mode) = EMACS_MODE.captures(variables) {
+            return Some(mode.get(1).unwrap().as_str());
+        }
+    }
+
+    VIM_MODELINE
+        .captures(line)
+        .and_then(|captures| VIM_FILETYPE.captures(captures.get(1).unwrap().as_str()))
+        .map(|captures| captures.get(1).unwrap().as_str())
+}
+
+/// The file type set by the first modeline in the given lines, if any.
+pub fn find_filetype<'a, I>(lines: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    lines.into_iter().filter_map(|line| filetype(line)).next()
+}
+
+/// The name or extension of the syntax for a file type, which can be passed to
+/// `SyntaxSet::find_syntax_by_token`.
+pub fn syntax_token(filetype: &str) -> &str {
+    ALIASES
+        .iter()
+        .find(|&&(alias, _)| alias.eq_ignore_ascii_case(filetype))
+        .map(|&(_, token)| token)
+        .unwrap_or(filetype)
+}
+
+/// The last `count` lines of the file at `path`, decoded like the rest of the input (as UTF-8 if
+/// `encoding` is `None`). Only the end of the file is read.
+pub fn last_lines(
+    path: &Path,
+    count: usize,
+    encoding: Option<&'static Encoding>,
+) -> io::Result<Vec<String>> {
+    const TAIL_SIZE: u64 = 4096;
+
+    let mut file = File::open(path)?;
+    let start = file.metadata()?.len().saturating_sub(TAIL_SIZE);
+    // Start at an even offset, such that UTF-16 code units are not split.
+    let start = start - start % 2;
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut tail = vec![];
+    file.read_to_end(&mut tail)?;
+    let tail = match encoding {
+        Some(encoding) => encoding.decode_with_bom_removal(&tail).0,
+        None => String::from_utf8_lossy(&tail),
+    };
+
+    let mut lines: Vec<&str> = tail.lines().collect();
+    if start > 0 && !lines.is_empty() {
+        // The first line is most likely incomplete.
+        lines.remove(0);
+    }
+
+    let skip = lines.len().saturating_sub(count);
+    Ok(lines[skip..].iter().map(|line| line.to_string()).collect())
+}
+
+#[test]
+fn test_filetype_vim() {
+    assert_eq!(Some("yaml"), filetype("# vim: ft=yaml"));
+    assert_eq!(Some("python"), filetype("# vim: set filetype=python ts=4 :"));
+    assert_eq!(Some("sh"), filetype("/* vim:sw=2:syntax=sh */"));
+    assert_eq!(Some("c"), filetype("vi: noet ft=c"));
+    assert_eq!(Some("ruby"), filetype("# vim600: ft=ruby"));
+    assert_eq!(None, filetype("# vim: ts=4"));
+    assert_eq!(None, filetype("# novim: ft=yaml"));
+}
+
+#[test]
+fn test_filetype_emacs() {
+    assert_eq!(Some("ruby"), filetype("# -*- mode: ruby -*-"));
+    assert_eq!(Some("c++"), filetype("// -*- Mode: c++; coding: utf-8 -*-"));
+    assert_eq!(Some("shell-script"), filetype("# -*-shell-script-*-"));
+    assert_eq!(None, filetype("# -*- coding: utf-8 -*-"));
+}
+
+#[test]
+fn test_find_filetype() {
+    let lines = vec![
+        "#!/bin/sh".to_owned(),
+        "# -*- mode: sh -*-".to_owned(),
+        "# vim: ft=bash".to_owned(),
+    ];
+    assert_eq!(Some("sh"), find_filetype(&lines));
+    assert_eq!(None, find_filetype(&lines[..1]));
+}
+
+#[test]
+fn test_syntax_token() {
+    assert_eq!("sh", syntax_token("shell-script"));
+    assert_eq!("ini", syntax_token("DOSINI"));
+    assert_eq!("yaml", syntax_token("yaml"));
+}
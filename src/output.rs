@@ -2,7 +2,7 @@ use std::env;
 use std::io::{self, Write};
 use std::process::{Child, Command, Stdio};
 
-use app::PagingMode;
+use config::PagingMode;
 use errors::*;
 
 pub enum OutputType {
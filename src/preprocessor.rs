@@ -0,0 +1,193 @@
+use std::borrow::Cow;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The number of columns from `cursor` to the next tab stop.
+fn tab_stop_distance(cursor: usize, tab_width: usize) -> usize {
+    tab_width - cursor % tab_width
+}
+
+/// Expand the tabs in `text` to spaces, up to the next multiple of `tab_width`. The `cursor` is
+/// the display column at which `text` starts (relative to the start of the line) and is advanced
+/// past it.
+pub fn expand_tabs<'a>(text: &'a str, tab_width: usize, cursor: &mut usize) -> Cow<'a, str> {
+    if !text.contains('\t') {
+        *cursor += text.width();
+        return Cow::Borrowed(text);
+    }
+
+    let mut expanded = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '\t' {
+            let distance = tab_stop_distance(*cursor, tab_width);
+            expanded.extend(::std::iter::repeat(' ').take(distance));
+            *cursor += distance;
+        } else {
+            expanded.push(c);
+            *cursor += c.width().unwrap_or(0);
+        }
+    }
+
+    Cow::Owned(expanded)
+}
+
+/// Get a visible replacement for a tab which starts at column `cursor`. If `tab_width` is
+/// non-zero, the replacement spans the columns up to the next tab stop.
+fn tab_glyph(cursor: usize, tab_width: usize) -> String {
+    if tab_width == 0 {
+        return "→".to_owned();
+    }
+
+    match tab_stop_distance(cursor, tab_width) {
+        1 => "→".to_owned(),
+        distance => format!("├{}┤", "─".repeat(distance - 2)),
+    }
+}
+
+/// Get a visible replacement for a non-printable character, if `c` is one.
+fn nonprintable_glyph(c: char) -> Option<String> {
+    let glyph = match c {
+        ' ' => "·".to_owned(),
+        '\r' => "␍".to_owned(),
+        // Keep the actual line break, such that the line still ends.
+        '\n' => "␊\n".to_owned(),
+        '\u{a0}' => "⍽".to_owned(),
+        // Control pictures: ␀, ␁, ..., ␛, ..., ␟
+        '\x00'..='\x1f' => {
+            ::std::char::from_u32(0x2400 + c as u32).map_or(String::new(), |c| c.to_string())
+        }
+        '\x7f' => "␡".to_owned(),
+        // Zero-width characters, which are not whitespace but just as invisible.
+        '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | '\u{feff}' => {
+            format!("\\u{{{:04X}}}", c as u32)
+        }
+        c if c.is_control() => format!("\\x{:02X}", c as u32),
+        c if c.is_whitespace() && !c.is_ascii() => format!("\\u{{{:04X}}}", c as u32),
+        _ => return None,
+    };
+
+    Some(glyph)
+}
+
+/// Split `text` into chunks of regular text and visible replacements of non-printable
+/// characters. The boolean flag is set for chunks that contain replacements. Tabs are replaced
+/// up to the next tab stop, like in `expand_tabs`.
+pub fn replace_nonprintable(
+    text: &str,
+    tab_width: usize,
+    cursor: &mut usize,
+) -> Vec<(String, bool)> {
+    let mut chunks: Vec<(String, bool)> = vec![];
+
+    for c in text.chars() {
+        let (glyph, nonprintable) = if c == '\t' {
+            (tab_glyph(*cursor, tab_width), true)
+        } else {
+            match nonprintable_glyph(c) {
+                Some(glyph) => (glyph, true),
+                None => (c.to_string(), false),
+            }
+        };
+
+        *cursor += glyph.width();
+
+        match chunks.last_mut() {
+            Some(&mut (ref mut chunk, flag)) if flag == nonprintable => {
+                chunk.push_str(&glyph);
+                continue;
+            }
+            _ => {}
+        }
+
+        chunks.push((glyph, nonprintable));
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+fn chunk(text: &str, nonprintable: bool) -> (String, bool) {
+    (text.to_owned(), nonprintable)
+}
+
+#[test]
+fn test_replace_nonprintable_printable() {
+    assert_eq!(
+        vec![chunk("main()", false)],
+        replace_nonprintable("main()", 0, &mut 0)
+    );
+}
+
+#[test]
+fn test_replace_nonprintable_whitespace() {
+    assert_eq!(
+        vec![
+            chunk("→", true),
+            chunk("let", false),
+            chunk("·", true),
+            chunk("x;", false),
+            chunk("··␍␊\n", true),
+        ],
+        replace_nonprintable("\tlet x;  \r\n", 0, &mut 0)
+    );
+}
+
+#[test]
+fn test_replace_nonprintable_control() {
+    assert_eq!(
+        vec![chunk("␀␛␡\\x85", true)],
+        replace_nonprintable("\x00\x1b\x7f\u{85}", 0, &mut 0)
+    );
+}
+
+#[test]
+fn test_replace_nonprintable_unicode_whitespace() {
+    assert_eq!(
+        vec![
+            chunk("a", false),
+            chunk("⍽", true),
+            chunk("b", false),
+            chunk("\\u{200B}\\u{2003}", true),
+        ],
+        replace_nonprintable("a\u{a0}b\u{200b}\u{2003}", 0, &mut 0)
+    );
+}
+
+#[test]
+fn test_replace_nonprintable_tab_stops() {
+    let mut cursor = 0;
+    assert_eq!(
+        vec![chunk("├──┤", true), chunk("ab", false), chunk("├┤", true)],
+        replace_nonprintable("\tab\t", 4, &mut cursor)
+    );
+    assert_eq!(8, cursor);
+
+    let mut cursor = 3;
+    assert_eq!(
+        vec![chunk("→", true)],
+        replace_nonprintable("\t", 4, &mut cursor)
+    );
+    assert_eq!(4, cursor);
+}
+
+#[test]
+fn test_expand_tabs() {
+    let mut cursor = 0;
+    assert_eq!("    a   bcd ", expand_tabs("\ta\tbcd\t", 4, &mut cursor));
+    assert_eq!(12, cursor);
+
+    // The cursor carries over from the previous text on the same line.
+    let mut cursor = 2;
+    assert_eq!("x     y", expand_tabs("x\ty", 8, &mut cursor));
+    assert_eq!(9, cursor);
+}
+
+#[test]
+fn test_expand_tabs_without_tabs() {
+    let mut cursor = 5;
+    match expand_tabs("no tabs", 4, &mut cursor) {
+        Cow::Borrowed(text) => assert_eq!("no tabs", text),
+        Cow::Owned(_) => panic!("text without tabs should not be copied"),
+    }
+    assert_eq!(12, cursor);
+}
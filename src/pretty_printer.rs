@@ -0,0 +1,282 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use console::Term;
+
+use encoding_rs::Encoding;
+
+use regex::Regex;
+
+use assets::{HighlightingAssets, BAT_THEME_DEFAULT};
+use config::{Config, PagingMode};
+use controller::Controller;
+use errors::*;
+use inputfile::InputFile;
+use line_range::LineRanges;
+use style::{OutputComponent, OutputComponents, OutputWrap};
+use syntax_mapping::SyntaxMapping;
+use walk::FileFilter;
+
+/// A builder for pretty-printing files from Rust code, without going through the command-line
+/// interface. By default, the output is colored but has no decorations and is never paged.
+pub struct PrettyPrinter<'a> {
+    config: Config<'a>,
+    assets: HighlightingAssets,
+}
+
+impl<'a> PrettyPrinter<'a> {
+    pub fn new() -> Self {
+        PrettyPrinter {
+            config: Config {
+                files: vec![],
+                file_names: vec![],
+                recursive: false,
+                file_filter: FileFilter::all(),
+                language: None,
+                syntax_mapping: SyntaxMapping::new(),
+                encoding: None,
+                term_width: Term::stdout().size().1 as usize,
+                loop_through: false,
+                colored_output: true,
+                true_color: true,
+                output_components: OutputComponents(HashSet::new()),
+                diff_base: None,
+                only_changed_lines: false,
+                diff_context: 3,
+                inline_diff: false,
+                blame_heatmap: false,
+                tab_width: 4,
+                show_nonprintable: false,
+                output_wrap: OutputWrap::None,
+                paging_mode: PagingMode::Never,
+                line_ranges: LineRanges::all(),
+                highlighted_lines: LineRanges::none(),
+                pattern: None,
+                only_matching_lines: false,
+                context: 0,
+                theme: String::from(BAT_THEME_DEFAULT),
+            },
+            assets: HighlightingAssets::new(),
+        }
+    }
+
+    /// Add a file which should be pretty-printed
+    pub fn input_file(&mut self, path: &'a str) -> &mut Self {
+        self.config.files.push(InputFile::Ordinary(path));
+        self
+    }
+
+    /// Add multiple files which should be pretty-printed
+    pub fn input_files<I>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        self.config
+            .files
+            .extend(paths.into_iter().map(InputFile::Ordinary));
+        self
+    }
+
+    /// Add a file as it is in the given Git revision (like 'HEAD~2'), read from the repository of
+    /// the current directory
+    pub fn input_file_from_revision(&mut self, revision: &'a str, path: &'a str) -> &mut Self {
+        self.config.files.push(InputFile::Revision(revision, path));
+        self
+    }
+
+    /// Add STDIN as an input
+    pub fn input_stdin(&mut self) -> &mut Self {
+        self.config.files.push(InputFile::StdIn);
+        self
+    }
+
+    /// Show the input which was added last under the given name, and use that name to detect
+    /// its syntax. If no input was added yet, the name is used for the first input.
+    pub fn file_name(&mut self, name: &'a str) -> &mut Self {
+        // Without any inputs, the name is stored at the position of the first one.
+        let index = self.config.files.len().saturating_sub(1);
+        self.config.file_names.resize(index + 1, None);
+        self.config.file_names[index] = Some(name);
+        self
+    }
+
+    /// Print the files inside of directories given as input, optionally filtered
+    pub fn recursive(&mut self, filter: FileFilter) -> &mut Self {
+        self.config.recursive = true;
+        self.config.file_filter = filter;
+        self
+    }
+
+    /// Explicitly set the language for syntax highlighting (name or file extension)
+    pub fn language(&mut self, language: &'a str) -> &mut Self {
+        self.config.language = Some(language);
+        self
+    }
+
+    /// Highlight files matching certain patterns with the given syntax, unless the language is
+    /// set explicitly
+    pub fn syntax_mapping(&mut self, mapping: SyntaxMapping) -> &mut Self {
+        self.config.syntax_mapping = mapping;
+        self
+    }
+
+    /// The encoding of input files without a byte order mark (default: UTF-8)
+    pub fn encoding(&mut self, encoding: &'static Encoding) -> &mut Self {
+        self.config.encoding = Some(encoding);
+        self
+    }
+
+    /// The character width of the terminal (default: autodetect)
+    pub fn term_width(&mut self, width: usize) -> &mut Self {
+        self.config.term_width = width;
+        self
+    }
+
+    /// Whether or not the output should be colorized (default: true)
+    pub fn colored_output(&mut self, yes: bool) -> &mut Self {
+        self.config.colored_output = yes;
+        self
+    }
+
+    /// Whether or not to output 24bit colors (default: true)
+    pub fn true_color(&mut self, yes: bool) -> &mut Self {
+        self.config.true_color = yes;
+        self
+    }
+
+    /// Whether to show a header with the file name
+    pub fn header(&mut self, yes: bool) -> &mut Self {
+        self.set_component(OutputComponent::Header, yes)
+    }
+
+    /// Whether to show line numbers
+    pub fn line_numbers(&mut self, yes: bool) -> &mut Self {
+        self.set_component(OutputComponent::Numbers, yes)
+    }
+
+    /// Whether to paint a grid, separating line numbers, Git changes and the code
+    pub fn grid(&mut self, yes: bool) -> &mut Self {
+        self.set_component(OutputComponent::Grid, yes)
+    }
+
+    /// Whether to show modification markers for files tracked by Git
+    pub fn vcs_modification_markers(&mut self, yes: bool) -> &mut Self {
+        self.set_component(OutputComponent::Changes, yes)
+    }
+
+    /// Whether to show the commit, author and age of every line of files tracked by Git
+    pub fn blame(&mut self, yes: bool) -> &mut Self {
+        self.set_component(OutputComponent::Blame, yes)
+    }
+
+    /// Color the blame of every line by its age, from recent to old (default: false)
+    pub fn blame_heatmap(&mut self, yes: bool) -> &mut Self {
+        self.config.blame_heatmap = yes;
+        self
+    }
+
+    /// Show the modifications compared to the given revision (like 'origin/master') instead of
+    /// `HEAD`
+    pub fn diff_base(&mut self, revision: &'a str) -> &mut Self {
+        self.config.diff_base = Some(revision);
+        self
+    }
+
+    /// Only print the lines that have been modified in Git, with `context` lines before and after
+    /// them. Files without modifications are skipped.
+    pub fn only_changed_lines(&mut self, context: usize) -> &mut Self {
+        self.config.only_changed_lines = true;
+        self.config.diff_context = context;
+        self
+    }
+
+    /// Whether to show the lines that have been removed in Git, at the position where they were
+    /// removed (default: false)
+    pub fn inline_diff(&mut self, yes: bool) -> &mut Self {
+        self.config.inline_diff = yes;
+        self
+    }
+
+    /// The width of a tab stop, or zero to print tabs as they are (default: 4)
+    pub fn tab_width(&mut self, width: usize) -> &mut Self {
+        self.config.tab_width = width;
+        self
+    }
+
+    /// Whether to show non-printable characters like tabs, spaces and line endings
+    /// (default: false)
+    pub fn show_nonprintable(&mut self, yes: bool) -> &mut Self {
+        self.config.show_nonprintable = yes;
+        self
+    }
+
+    /// Text wrapping mode (default: no wrapping)
+    pub fn wrapping_mode(&mut self, mode: OutputWrap) -> &mut Self {
+        self.config.output_wrap = mode;
+        self
+    }
+
+    /// Pager or STDOUT (default: never page). Only used by `print`.
+    pub fn paging_mode(&mut self, mode: PagingMode) -> &mut Self {
+        self.config.paging_mode = mode;
+        self
+    }
+
+    /// Only print the given ranges of lines
+    pub fn line_ranges(&mut self, ranges: LineRanges) -> &mut Self {
+        self.config.line_ranges = ranges;
+        self
+    }
+
+    /// Highlight the given ranges of lines with a background (default: none)
+    pub fn highlighted_lines(&mut self, ranges: LineRanges) -> &mut Self {
+        self.config.highlighted_lines = ranges;
+        self
+    }
+
+    /// Highlight all matches of the given pattern
+    pub fn pattern(&mut self, pattern: Regex) -> &mut Self {
+        self.config.pattern = Some(pattern);
+        self
+    }
+
+    /// Only print the lines that match the pattern, with `context` lines before and after them.
+    /// Groups of lines are separated by a snip marker if the grid is shown.
+    pub fn only_matching_lines(&mut self, context: usize) -> &mut Self {
+        self.config.only_matching_lines = true;
+        self.config.context = context;
+        self
+    }
+
+    /// Specify the highlighting theme
+    pub fn theme(&mut self, theme: &str) -> &mut Self {
+        self.config.theme = theme.to_owned();
+        self
+    }
+
+    /// Pretty-print all specified inputs to STDOUT (or the pager). Returns `Ok(false)` if any
+    /// of the inputs could not be printed.
+    pub fn print(&self) -> Result<bool> {
+        Controller::new(&self.config, &self.assets).run()
+    }
+
+    /// Pretty-print all specified inputs into the given writer.
+    pub fn print_with_writer(&self, writer: &mut Write) -> Result<bool> {
+        Controller::new(&self.config, &self.assets).run_with_writer(writer)
+    }
+
+    fn set_component(&mut self, component: OutputComponent, yes: bool) -> &mut Self {
+        if yes {
+            self.config.output_components.0.insert(component);
+        } else {
+            self.config.output_components.0.remove(&component);
+        }
+        self
+    }
+}
+
+impl<'a> Default for PrettyPrinter<'a> {
+    fn default() -> Self {
+        PrettyPrinter::new()
+    }
+}
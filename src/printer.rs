@@ -1,27 +1,47 @@
+use std::borrow::Cow;
 use std::boxed::Box;
+use std::collections::HashMap;
 use std::io::Write;
 use std::vec::Vec;
 
-use ansi_term::Colour::{Fixed, Green, Red, Yellow};
+use ansi_term::Colour::{Cyan, Fixed, Green, Red, Yellow};
 use ansi_term::Style;
 
 use console::AnsiCodeIterator;
 
+use content_inspector::ContentType;
+
+use encoding_rs::Encoding;
+
 use syntect::easy::HighlightLines;
-use syntect::highlighting::Theme;
+use syntect::highlighting::{self, FontStyle, Theme};
+use syntect::parsing::SyntaxDefinition;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-use app::{Config, InputFile};
 use assets::HighlightingAssets;
-use decorations::{Decoration, GridBorderDecoration, LineChangesDecoration, LineNumberDecoration};
-use diff::get_git_diff;
+use compression::Compression;
+use config::Config;
+use blame::{get_blame, LineBlames};
+use decorations::{
+    BlameDecoration, Decoration, GridBorderDecoration, LineChangesDecoration,
+    LineNumberDecoration,
+};
 use diff::LineChanges;
+use diff::{get_removed_lines, RemovedHunk, RemovedLines};
 use errors::*;
+use inputfile::{InputFile, InputFileReader};
+use line_range::{LineRanges, RangeCheckResult};
+use preprocessor::{expand_tabs, replace_nonprintable};
 use style::OutputWrap;
 use terminal::{as_terminal_escaped, to_ansi_color};
+use wrap::{wrap, WrapAction};
 
 pub trait Printer {
     fn print_header(&mut self, handle: &mut Write, file: InputFile) -> Result<()>;
     fn print_footer(&mut self, handle: &mut Write) -> Result<()>;
+    fn print_snip(&mut self, handle: &mut Write) -> Result<()>;
     fn print_line(
         &mut self,
         out_of_range: bool,
@@ -48,6 +68,10 @@ impl Printer for SimplePrinter {
         Ok(())
     }
 
+    fn print_snip(&mut self, _handle: &mut Write) -> Result<()> {
+        Ok(())
+    }
+
     fn print_line(
         &mut self,
         out_of_range: bool,
@@ -64,16 +88,125 @@ impl Printer for SimplePrinter {
 
 pub struct InteractivePrinter<'a> {
     colors: Colors,
+    nonprintable_style: highlighting::Style,
+    line_highlight_color: highlighting::Color,
+    match_foreground_color: Option<highlighting::Color>,
+    match_background_color: highlighting::Color,
     config: &'a Config<'a>,
+    file_name: Option<String>,
     decorations: Vec<Box<Decoration>>,
     panel_width: usize,
     ansi_prefix_sgr: String,
+    highlighted_lines: LineRanges,
+    content_type: ContentType,
+    compression: Option<Compression>,
+    encoding: Option<&'static Encoding>,
     pub line_changes: Option<LineChanges>,
+    pub blame: Option<LineBlames>,
     highlighter: HighlightLines<'a>,
+    inline_diff: Option<InlineDiff<'a>>,
+}
+
+/// The lines that have been removed from the input, which are printed in between the current
+/// lines. They are highlighted separately, in the context of the old version of the file.
+struct InlineDiff<'a> {
+    old_lines: Vec<String>,
+    hunks: HashMap<u32, RemovedHunk>,
+    highlighter: HighlightLines<'a>,
+
+    /// The number of old lines that have been passed to the highlighter
+    num_highlighted: usize,
+
+    /// The number of the next line of the input, and whether the line before it was printed
+    next_line_number: usize,
+    previous_line_in_range: bool,
+}
+
+impl<'a> InlineDiff<'a> {
+    fn new(
+        removed: RemovedLines,
+        encoding: Option<&'static Encoding>,
+        syntax: &'a SyntaxDefinition,
+        theme: &'a Theme,
+    ) -> Self {
+        InlineDiff {
+            old_lines: decode_lines(removed.old_content, encoding).unwrap_or_default(),
+            hunks: removed.hunks,
+            highlighter: HighlightLines::new(syntax, theme),
+            num_highlighted: 0,
+            next_line_number: 1,
+            previous_line_in_range: false,
+        }
+    }
+
+    /// The removed lines in front of the line `line_number` of the input, if they should be
+    /// printed. Removed lines are printed next to a printed line, but replaced lines only together
+    /// with the lines that replaced them.
+    fn hunk_before(&mut self, line_number: usize, out_of_range: bool) -> Option<RemovedHunk> {
+        let previous_line_in_range = self.previous_line_in_range;
+        self.previous_line_in_range = !out_of_range;
+        self.next_line_number = line_number + 1;
+
+        let hunk = *self.hunks.get(&(line_number as u32))?;
+        if !out_of_range || (previous_line_in_range && !hunk.modified) {
+            Some(hunk)
+        } else {
+            None
+        }
+    }
+
+    /// Highlight the old line with the given (zero-based) index. All lines before it are
+    /// highlighted first, such that it is highlighted in its original context.
+    fn highlight(&mut self, index: usize) -> Option<(String, Vec<(highlighting::Style, String)>)> {
+        let line = self.old_lines.get(index)?.clone();
+
+        while self.num_highlighted < index {
+            self.highlighter
+                .highlight(&self.old_lines[self.num_highlighted]);
+            self.num_highlighted += 1;
+        }
+
+        let regions = self
+            .highlighter
+            .highlight(&line)
+            .into_iter()
+            .map(|(style, text)| (style, text.to_owned()))
+            .collect();
+        self.num_highlighted = index + 1;
+
+        Some((line, regions))
+    }
+}
+
+/// Split the old version of a file into lines, decoded like the input itself (see
+/// `InputFileReader::decode`). Every line ends with a newline.
+fn decode_lines(content: Vec<u8>, encoding: Option<&'static Encoding>) -> Result<Vec<String>> {
+    let mut reader = InputFileReader::from_bytes(content)?;
+    reader.decode(encoding)?;
+
+    let mut lines = vec![];
+    let mut buffer = vec![];
+    while reader.read_line(&mut buffer)? {
+        let mut line = String::from_utf8_lossy(&buffer).into_owned();
+        if !line.ends_with('\n') {
+            line.push('\n');
+        }
+        lines.push(line);
+        buffer.clear();
+    }
+
+    Ok(lines)
 }
 
 impl<'a> InteractivePrinter<'a> {
-    pub fn new(config: &'a Config, assets: &'a HighlightingAssets, file: InputFile) -> Self {
+    pub fn new(
+        config: &'a Config,
+        assets: &'a HighlightingAssets,
+        file: InputFile,
+        file_name: Option<&str>,
+        reader: &InputFileReader,
+        line_changes: Option<LineChanges>,
+    ) -> Self {
         let theme = assets.get_theme(&config.theme);
 
         let colors = if config.colored_output {
@@ -82,9 +215,33 @@ impl<'a> InteractivePrinter<'a> {
             Colors::plain()
         };
 
+        let nonprintable_style = highlighting::Style {
+            foreground: theme
+                .settings
+                .gutter_foreground
+                .unwrap_or(DEFAULT_NONPRINTABLE_COLOR),
+            background: theme.settings.background.unwrap_or(highlighting::Color::BLACK),
+            font_style: FontStyle::empty(),
+        };
+
+        let line_highlight_color = theme
+            .settings
+            .line_highlight
+            .unwrap_or(DEFAULT_LINE_HIGHLIGHT_COLOR);
+
+        let highlighted_lines = match reader.num_lines {
+            Some(num_lines) => config.highlighted_lines.resolve(num_lines),
+            None => config.highlighted_lines.clone(),
+        };
+
         // Create decorations.
         let mut decorations: Vec<Box<Decoration>> = Vec::new();
 
+        if config.output_components.blame() {
+            let heatmap = config.blame_heatmap && config.colored_output;
+            decorations.push(Box::new(BlameDecoration::new(&colors, heatmap)));
+        }
+
         if config.output_components.numbers() {
             decorations.push(Box::new(LineNumberDecoration::new(&colors)));
         }
@@ -112,24 +269,70 @@ impl<'a> InteractivePrinter<'a> {
             panel_width = 0;
         }
 
-        // Get the Git modifications
-        let line_changes = match file {
-            InputFile::Ordinary(filename) => get_git_diff(filename),
+        // Blame every line, if requested
+        let blame = match file {
+            InputFile::Ordinary(filename) if config.output_components.blame() => {
+                get_blame(filename)
+            }
             _ => None,
         };
 
         // Determine the type of syntax for highlighting
-        let syntax = assets.get_syntax(config.language, file);
+        let syntax = assets.get_syntax(
+            config.language,
+            &config.syntax_mapping,
+            file,
+            file_name,
+            reader,
+        );
         let highlighter = HighlightLines::new(syntax, theme);
 
+        // Get the removed lines, which are highlighted with the same syntax
+        let inline_diff = match file {
+            InputFile::Ordinary(filename)
+                if config.inline_diff && !reader.content_type.is_binary() =>
+            {
+                get_removed_lines(filename, config.diff_base)
+                    .map(|removed| InlineDiff::new(removed, config.encoding, syntax, theme))
+            }
+            _ => None,
+        };
+
         InteractivePrinter {
             panel_width,
             colors,
+            nonprintable_style,
+            line_highlight_color,
+            match_foreground_color: theme.settings.find_highlight_foreground,
+            match_background_color: theme
+                .settings
+                .find_highlight
+                .unwrap_or(DEFAULT_MATCH_BACKGROUND_COLOR),
             config,
+            file_name: file_name.map(String::from),
             decorations,
             ansi_prefix_sgr: String::new(),
+            highlighted_lines,
+            content_type: reader.content_type,
+            compression: reader.compression,
+            encoding: reader.encoding,
             line_changes,
+            blame,
             highlighter,
+            inline_diff,
+        }
+    }
+
+    /// The prefix and the name of the input for the header. A name given with `--file-name`
+    /// replaces the actual one. Files from a Git revision are shown like 'REV:path'.
+    fn header_name<'b>(&'b self, file: InputFile<'b>) -> (&'static str, Cow<'b, str>) {
+        match (self.file_name.as_ref(), file) {
+            (Some(name), _) => ("File: ", Cow::Borrowed(name)),
+            (None, InputFile::Ordinary(filename)) => ("File: ", Cow::Borrowed(filename)),
+            (None, InputFile::Revision(revision, path)) => {
+                ("File: ", Cow::Owned(format!("{}:{}", revision, path)))
+            }
+            (None, _) => ("", Cow::Borrowed("STDIN")),
         }
     }
 
@@ -148,11 +351,128 @@ impl<'a> InteractivePrinter<'a> {
 
         Ok(())
     }
+
+    /// Split the highlighted regions of a line at the boundaries of the matches of the search
+    /// pattern. The parts inside of a match get the background (and possibly the foreground) of
+    /// search results from the theme, on top of their syntax highlighting.
+    fn overlay_matches<'b>(
+        &self,
+        line: &str,
+        regions: Vec<(highlighting::Style, &'b str)>,
+    ) -> Vec<(highlighting::Style, &'b str, Option<highlighting::Color>)> {
+        let matches = match self.config.pattern {
+            Some(ref pattern) => pattern
+                .find_iter(line.trim_right_matches(|c| c == '\r' || c == '\n'))
+                .filter(|m| m.start() < m.end())
+                .map(|m| (m.start(), m.end()))
+                .collect::<Vec<_>>(),
+            None => vec![],
+        };
+
+        if matches.is_empty() {
+            return regions
+                .into_iter()
+                .map(|(style, text)| (style, text, None))
+                .collect();
+        }
+
+        let mut overlaid = Vec::with_capacity(regions.len() + 2 * matches.len());
+        let mut offset = 0;
+
+        for (style, text) in regions {
+            let (start, end) = (offset, offset + text.len());
+            offset = end;
+
+            // Cut the region into parts outside and inside of the matches.
+            let mut position = start;
+            for &(match_start, match_end) in &matches {
+                if match_end <= position || match_start >= end {
+                    continue;
+                }
+
+                if match_start > position {
+                    overlaid.push((style, &text[position - start..match_start - start], None));
+                    position = match_start;
+                }
+
+                let match_end = match_end.min(end);
+                let match_style = highlighting::Style {
+                    foreground: self.match_foreground_color.unwrap_or(style.foreground),
+                    ..style
+                };
+                overlaid.push((
+                    match_style,
+                    &text[position - start..match_end - start],
+                    Some(self.match_background_color),
+                ));
+                position = match_end;
+            }
+
+            if position < end {
+                overlaid.push((style, &text[position - start..], None));
+            }
+        }
+
+        overlaid
+    }
+
+    /// Expand the tabs in the highlighted regions of a line and, if requested, replace the
+    /// non-printable characters by visible glyphs, which are painted in their own style. Tab stops
+    /// are relative to the start of the line contents, not to the start of the terminal line
+    /// (which includes the decoration panel).
+    fn preprocess<'b>(
+        &self,
+        regions: Vec<(highlighting::Style, &'b str, Option<highlighting::Color>)>,
+    ) -> Vec<(highlighting::Style, Cow<'b, str>, Option<highlighting::Color>)> {
+        let tab_width = self.config.tab_width;
+        let mut cursor = 0;
+        let mut processed = Vec::with_capacity(regions.len());
+
+        for (style, text, background_color) in regions {
+            if self.config.show_nonprintable {
+                for (chunk, nonprintable) in replace_nonprintable(text, tab_width, &mut cursor) {
+                    let style = if nonprintable {
+                        self.nonprintable_style
+                    } else {
+                        style
+                    };
+                    processed.push((style, Cow::Owned(chunk), background_color));
+                }
+            } else if tab_width > 0 {
+                processed.push((
+                    style,
+                    expand_tabs(text, tab_width, &mut cursor),
+                    background_color,
+                ));
+            } else {
+                processed.push((style, Cow::Borrowed(text), background_color));
+            }
+        }
+
+        processed
+    }
+
+    /// Blank space of the given width in the given background color
+    fn padding(&self, background: highlighting::Color, width: usize) -> String {
+        Style::new()
+            .on(to_ansi_color(background, self.config.true_color))
+            .paint(" ".repeat(width))
+            .to_string()
+    }
 }
 
 impl<'a> Printer for InteractivePrinter<'a> {
     fn print_header(&mut self, handle: &mut Write, file: InputFile) -> Result<()> {
         if !self.config.output_components.header() {
+            if self.content_type.is_binary() {
+                use ansi_term::Colour::Yellow;
+                let (_, name) = self.header_name(file);
+                eprintln!(
+                    "{}: Binary content from '{}' will not be printed to the terminal.",
+                    Yellow.paint("[bat warning]"),
+                    name
+                );
+            }
             return Ok(());
         }
 
@@ -171,28 +491,84 @@ impl<'a> Printer for InteractivePrinter<'a> {
             write!(handle, "{}", " ".repeat(self.panel_width))?;
         }
 
-        let (prefix, name) = match file {
-            InputFile::Ordinary(filename) => ("File: ", filename),
-            _ => ("", "STDIN"),
-        };
+        let (prefix, name) = self.header_name(file);
 
-        writeln!(handle, "{}{}", prefix, self.colors.filename.paint(name))?;
+        let mut notes = vec![];
+        if let Some(compression) = self.compression {
+            notes.push(compression.name());
+        }
+        if self.content_type.is_binary() {
+            notes.push("BINARY");
+        }
+        if let Some(encoding) = self.encoding {
+            notes.push(encoding.name());
+        }
+        let mode: String = notes.iter().map(|note| format!("   <{}>", note)).collect();
+
+        writeln!(
+            handle,
+            "{}{}{}",
+            prefix,
+            self.colors.filename.paint(name),
+            mode
+        )?;
 
         if self.config.output_components.grid() {
-            self.print_horizontal_line(handle, '┼')?;
+            if self.content_type.is_binary() {
+                self.print_horizontal_line(handle, '┴')?;
+            } else {
+                self.print_horizontal_line(handle, '┼')?;
+            }
         }
 
         Ok(())
     }
 
     fn print_footer(&mut self, handle: &mut Write) -> Result<()> {
-        if self.config.output_components.grid() {
+        // Lines removed at the end of the file
+        if let Some(line_number) = self.inline_diff.as_ref().map(|d| d.next_line_number) {
+            self.print_removed_lines(handle, line_number, true)?;
+        }
+
+        if self.config.output_components.grid() && !self.content_type.is_binary() {
             self.print_horizontal_line(handle, '┴')
         } else {
             Ok(())
         }
     }
 
+    fn print_snip(&mut self, handle: &mut Write) -> Result<()> {
+        // Without the grid, chunks are separated like in the output of grep.
+        if !self.config.output_components.grid() {
+            writeln!(handle, "{}", self.colors.grid.paint("--"))?;
+            return Ok(());
+        }
+
+        let panel = if self.panel_width > 0 {
+            format!("{:>width$} │ ", "...", width = self.panel_width - 1)
+        } else {
+            String::new()
+        };
+
+        let title = " 8< ";
+        let remaining = self
+            .config
+            .term_width
+            .saturating_sub(panel.chars().count() + title.len());
+        let snip_left = "─".repeat(remaining / 2);
+        let snip_right = "─".repeat(remaining - remaining / 2);
+
+        writeln!(
+            handle,
+            "{}",
+            self.colors
+                .grid
+                .paint(format!("{}{}{}{}", panel, snip_left, title, snip_right))
+        )?;
+
+        Ok(())
+    }
+
     fn print_line(
         &mut self,
         out_of_range: bool,
@@ -203,11 +579,82 @@ impl<'a> Printer for InteractivePrinter<'a> {
         let line = String::from_utf8_lossy(&line_buffer);
         let regions = self.highlighter.highlight(line.as_ref());
 
+        self.print_removed_lines(handle, line_number, out_of_range)?;
+
         if out_of_range {
             return Ok(());
         }
 
-        let mut cursor: usize = 0;
+        let regions = self.overlay_matches(&line, regions);
+        let regions = self.preprocess(regions);
+
+        // Highlighted lines get a background over the whole width of the terminal.
+        let background_color = if self.config.colored_output
+            && self.highlighted_lines.check(line_number) == RangeCheckResult::InRange
+        {
+            Some(self.line_highlight_color)
+        } else {
+            None
+        };
+
+        self.print_regions(handle, line_number, false, regions, background_color)
+    }
+}
+
+impl<'a> InteractivePrinter<'a> {
+    /// Print the lines that have been removed in front of the line `line_number` of the input,
+    /// without line numbers and with a red background.
+    fn print_removed_lines(
+        &mut self,
+        handle: &mut Write,
+        line_number: usize,
+        out_of_range: bool,
+    ) -> Result<()> {
+        let hunk = match self.inline_diff.as_mut() {
+            Some(inline_diff) => inline_diff.hunk_before(line_number, out_of_range),
+            None => None,
+        };
+        let hunk = match hunk {
+            Some(hunk) => hunk,
+            None => return Ok(()),
+        };
+
+        let background_color = if self.config.colored_output {
+            Some(DEFAULT_REMOVED_LINE_COLOR)
+        } else {
+            None
+        };
+
+        let first_index = hunk.first_line.saturating_sub(1) as usize;
+        for index in first_index..first_index + hunk.num_lines as usize {
+            let (line, regions) = match self.inline_diff.as_mut().and_then(|d| d.highlight(index)) {
+                Some(highlighted) => highlighted,
+                None => break,
+            };
+            let regions = regions
+                .iter()
+                .map(|&(style, ref text)| (style, text.as_str()))
+                .collect();
+
+            let regions = self.overlay_matches(&line, regions);
+            let regions = self.preprocess(regions);
+
+            self.print_regions(handle, line_number, true, regions, background_color)?;
+        }
+
+        Ok(())
+    }
+
+    /// Print the decorations and the highlighted regions of a line. Removed lines get empty
+    /// decorations, like the continuation of a wrapped line.
+    fn print_regions(
+        &mut self,
+        handle: &mut Write,
+        line_number: usize,
+        removed: bool,
+        regions: Vec<(highlighting::Style, Cow<str>, Option<highlighting::Color>)>,
+        background_color: Option<highlighting::Color>,
+    ) -> Result<()> {
         let mut cursor_max: usize = self.config.term_width;
         let mut panel_wrap: Option<String> = None;
 
@@ -216,7 +663,7 @@ impl<'a> Printer for InteractivePrinter<'a> {
             let decorations = self
                 .decorations
                 .iter()
-                .map(|ref d| d.generate(line_number, false, self))
+                .map(|ref d| d.generate(line_number, removed, self))
                 .collect::<Vec<_>>();
 
             for deco in decorations {
@@ -230,21 +677,68 @@ impl<'a> Printer for InteractivePrinter<'a> {
             let true_color = self.config.true_color;
             let colored_output = self.config.colored_output;
 
-            write!(
-                handle,
-                "{}",
-                regions
-                    .iter()
-                    .map(|&(style, text)| as_terminal_escaped(
-                        style,
-                        text,
-                        true_color,
-                        colored_output,
-                    )).collect::<Vec<_>>()
-                    .join("")
-            )?;
+            if let Some(background) = background_color {
+                // The line ending is only written after the padding.
+                let mut cursor = 0;
+                for &(style, ref text, region_background_color) in regions.iter() {
+                    let text = text.trim_right_matches(|c| c == '\r' || c == '\n');
+                    cursor += text.width();
+
+                    write!(
+                        handle,
+                        "{}",
+                        as_terminal_escaped(
+                            style,
+                            text,
+                            true_color,
+                            colored_output,
+                            region_background_color.or(background_color),
+                        )
+                    )?;
+                }
+
+                writeln!(
+                    handle,
+                    "{}",
+                    self.padding(background, cursor_max.saturating_sub(cursor))
+                )?;
+            } else {
+                write!(
+                    handle,
+                    "{}",
+                    regions
+                        .iter()
+                        .map(|&(style, ref text, region_background_color)| {
+                            as_terminal_escaped(
+                                style,
+                                text,
+                                true_color,
+                                colored_output,
+                                region_background_color,
+                            )
+                        }).collect::<Vec<_>>()
+                        .join("")
+                )?;
+            }
         } else {
-            for &(style, region) in regions.iter() {
+            let mut cursor: usize = 0;
+
+            // Decide where to wrap for the whole line first, such that words which span several
+            // highlighted regions are kept together. Wrapping is based on the display width and
+            // never splits a grapheme cluster (like a base character and its combining marks).
+            let graphemes = regions
+                .iter()
+                .flat_map(|&(_, ref region, _)| AnsiCodeIterator::new(region))
+                .filter(|&(_, is_ansi)| !is_ansi)
+                .flat_map(|(text, _)| {
+                    text.trim_right_matches(|c| c == '\r' || c == '\n')
+                        .graphemes(true)
+                }).collect::<Vec<_>>();
+            let mut wrap_actions =
+                wrap(&graphemes, cursor_max, self.config.output_wrap).into_iter();
+
+            for &(style, ref region, region_background_color) in regions.iter() {
+                let region_background_color = region_background_color.or(background_color);
                 let mut ansi_iterator = AnsiCodeIterator::new(region);
                 let mut ansi_prefix: String = String::new();
                 for chunk in ansi_iterator {
@@ -266,31 +760,18 @@ impl<'a> Printer for InteractivePrinter<'a> {
                         // Regular text.
                         (text, false) => {
                             let text = text.trim_right_matches(|c| c == '\r' || c == '\n');
-                            let mut chars = text.chars();
-                            let mut remaining = text.chars().count();
-
-                            while remaining > 0 {
-                                let available = cursor_max - cursor;
-
-                                // It fits.
-                                if remaining <= available {
-                                    let text = chars.by_ref().take(remaining).collect::<String>();
-                                    cursor += remaining;
-
-                                    write!(
-                                        handle,
-                                        "{}",
-                                        as_terminal_escaped(
-                                            style,
-                                            &*format!(
-                                                "{}{}{}",
-                                                self.ansi_prefix_sgr, ansi_prefix, text
-                                            ),
-                                            self.config.true_color,
-                                            self.config.colored_output,
-                                        )
-                                    )?;
-                                    break;
+                            let mut segment = String::new();
+
+                            for grapheme in text.graphemes(true) {
+                                match wrap_actions.next() {
+                                    // It fits.
+                                    Some(WrapAction::Print) | None => {
+                                        segment.push_str(grapheme);
+                                        cursor += grapheme.width();
+                                        continue;
+                                    }
+                                    Some(WrapAction::Skip) => continue,
+                                    Some(WrapAction::Wrap) => {}
                                 }
 
                                 // Generate wrap padding if not already generated.
@@ -311,24 +792,49 @@ impl<'a> Printer for InteractivePrinter<'a> {
                                 }
 
                                 // It wraps.
-                                let text = chars.by_ref().take(available).collect::<String>();
-                                cursor = 0;
-                                remaining -= available;
-
                                 write!(
                                     handle,
-                                    "{}\n{}",
+                                    "{}{}\n{}",
                                     as_terminal_escaped(
                                         style,
                                         &*format!(
                                             "{}{}{}",
-                                            self.ansi_prefix_sgr, ansi_prefix, text
+                                            self.ansi_prefix_sgr, ansi_prefix, segment
                                         ),
                                         self.config.true_color,
                                         self.config.colored_output,
+                                        region_background_color,
                                     ),
+                                    match background_color {
+                                        Some(background) => self.padding(
+                                            background,
+                                            cursor_max.saturating_sub(cursor),
+                                        ),
+                                        None => String::new(),
+                                    },
                                     panel_wrap.clone().unwrap()
                                 )?;
+
+                                segment.clear();
+                                segment.push_str(grapheme);
+                                cursor = grapheme.width();
+                            }
+
+                            if !segment.is_empty() {
+                                write!(
+                                    handle,
+                                    "{}",
+                                    as_terminal_escaped(
+                                        style,
+                                        &*format!(
+                                            "{}{}{}",
+                                            self.ansi_prefix_sgr, ansi_prefix, segment
+                                        ),
+                                        self.config.true_color,
+                                        self.config.colored_output,
+                                        region_background_color,
+                                    )
+                                )?;
                             }
 
                             // Clear the ANSI prefix buffer.
@@ -338,6 +844,14 @@ impl<'a> Printer for InteractivePrinter<'a> {
                 }
             }
 
+            if let Some(background) = background_color {
+                write!(
+                    handle,
+                    "{}",
+                    self.padding(background, cursor_max.saturating_sub(cursor))
+                )?;
+            }
+
             write!(handle, "\n")?;
         }
 
@@ -347,6 +861,38 @@ impl<'a> Printer for InteractivePrinter<'a> {
 
 const DEFAULT_GUTTER_COLOR: u8 = 238;
 
+/// The 24 bit equivalent of `DEFAULT_GUTTER_COLOR`
+const DEFAULT_NONPRINTABLE_COLOR: highlighting::Color = highlighting::Color {
+    r: 0x44,
+    g: 0x44,
+    b: 0x44,
+    a: 0xff,
+};
+
+/// The background of search pattern matches if the theme does not define one
+const DEFAULT_MATCH_BACKGROUND_COLOR: highlighting::Color = highlighting::Color {
+    r: 0x87,
+    g: 0x5f,
+    b: 0x00,
+    a: 0xff,
+};
+
+/// The background of highlighted lines if the theme does not define one
+const DEFAULT_LINE_HIGHLIGHT_COLOR: highlighting::Color = highlighting::Color {
+    r: 0x3a,
+    g: 0x3a,
+    b: 0x3a,
+    a: 0xff,
+};
+
+/// The background of lines that have been removed in Git
+const DEFAULT_REMOVED_LINE_COLOR: highlighting::Color = highlighting::Color {
+    r: 0x5f,
+    g: 0x00,
+    b: 0x00,
+    a: 0xff,
+};
+
 #[derive(Default)]
 pub struct Colors {
     pub grid: Style,
@@ -354,6 +900,7 @@ pub struct Colors {
     pub git_added: Style,
     pub git_removed: Style,
     pub git_modified: Style,
+    pub git_staged: Style,
     pub line_number: Style,
 }
 
@@ -375,6 +922,7 @@ impl Colors {
             git_added: Green.normal(),
             git_removed: Red.normal(),
             git_modified: Yellow.normal(),
+            git_staged: Cyan.normal(),
             line_number: gutter_color.normal(),
         }
     }
@@ -0,0 +1,143 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+use toml;
+
+use errors::*;
+use syntax_mapping::SyntaxMapping;
+
+/// The name of the project configuration file at the root of a Git repository
+pub const PROJECT_CONFIG_FILE_NAME: &str = ".bat.toml";
+
+/// The contents of a project configuration file, as written by the user. Unknown keys are
+/// ignored.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ProjectConfigFile {
+    tabs: Option<usize>,
+    theme: Option<String>,
+    style: Option<String>,
+    #[serde(default)]
+    map_syntax: Vec<String>,
+}
+
+/// Project-wide defaults, read from a `.bat.toml` file at the root of a Git repository. These
+/// settings only apply if they are not set on the command line or in the user configuration.
+#[derive(Debug, Default)]
+pub struct ProjectConfig {
+    /// The path of the file these settings were read from
+    pub path: PathBuf,
+
+    /// The tab width (`tabs = 2`)
+    pub tab_width: Option<usize>,
+
+    /// The highlighting theme (`theme = "TwoDark"`)
+    pub theme: Option<String>,
+
+    /// A comma-separated list of style components (`style = "numbers,changes"`)
+    pub style: Option<String>,
+
+    /// Syntax mappings (`map-syntax = ["*.conf:INI"]`)
+    pub syntax_mapping: SyntaxMapping,
+}
+
+impl ProjectConfig {
+    /// The path of the project configuration file of the Git repository containing `dir`, if
+    /// there is one.
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        let repo = Repository::discover(dir).ok()?;
+        let path = repo.workdir()?.join(PROJECT_CONFIG_FILE_NAME);
+
+        if path.is_file() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    /// Read the project configuration of the Git repository containing `dir`. Outside of a
+    /// repository, or if the repository has no configuration file, this returns `None`.
+    pub fn discover(dir: &Path) -> Result<Option<ProjectConfig>> {
+        match ProjectConfig::find(dir) {
+            Some(path) => ProjectConfig::from_file(&path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<ProjectConfig> {
+        let mut contents = String::new();
+        File::open(path)?.read_to_string(&mut contents)?;
+
+        let mut config = ProjectConfig::from_str(&contents).map_err(|error| -> Error {
+            format!(
+                "Invalid project configuration file '{}': {}",
+                path.to_string_lossy(),
+                error
+            ).into()
+        })?;
+        config.path = path.to_owned();
+
+        Ok(config)
+    }
+
+    fn from_str(contents: &str) -> Result<ProjectConfig> {
+        let file: ProjectConfigFile = toml::from_str(contents)?;
+
+        let mut syntax_mapping = SyntaxMapping::new();
+        for mapping in &file.map_syntax {
+            syntax_mapping.insert_from_str(mapping)?;
+        }
+
+        Ok(ProjectConfig {
+            path: PathBuf::new(),
+            tab_width: file.tabs,
+            theme: file.theme,
+            style: file.style,
+            syntax_mapping,
+        })
+    }
+}
+
+#[test]
+fn test_from_str() {
+    let config = ProjectConfig::from_str(
+        "
+        tabs = 2
+        theme = \"Monokai Extended\"
+        style = \"numbers,changes\"
+        map-syntax = [\"*.conf:INI\", \"Jenkinsfile:Groovy\"]
+        ",
+    ).unwrap();
+
+    assert_eq!(Some(2), config.tab_width);
+    assert_eq!(Some("Monokai Extended"), config.theme.as_ref().map(String::as_str));
+    assert_eq!(Some("numbers,changes"), config.style.as_ref().map(String::as_str));
+    assert_eq!(
+        Some("Groovy"),
+        config.syntax_mapping.get_language(Path::new("Jenkinsfile"))
+    );
+}
+
+#[test]
+fn test_from_str_empty() {
+    let config = ProjectConfig::from_str("").unwrap();
+
+    assert_eq!(None, config.tab_width);
+    assert_eq!(None, config.theme);
+    assert_eq!(None, config.style);
+}
+
+#[test]
+fn test_from_str_unknown_key() {
+    let config = ProjectConfig::from_str("paging = \"never\"\ntabs = 2").unwrap();
+
+    assert_eq!(Some(2), config.tab_width);
+}
+
+#[test]
+fn test_from_str_invalid() {
+    assert!(ProjectConfig::from_str("tabs = \"two\"").is_err());
+    assert!(ProjectConfig::from_str("map-syntax = [\"*.conf\"]").is_err());
+}
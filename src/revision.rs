@@ -0,0 +1,86 @@
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use git2::Repository;
+
+use errors::*;
+
+/// Read the contents of the file at `path` (relative to the current directory) in the given Git
+/// revision, straight from the repository.
+pub fn read_file(revision: &str, path: &str) -> Result<Vec<u8>> {
+    let current_dir = fs::canonicalize(env::current_dir()?)?;
+    let repo = Repository::discover(&current_dir).map_err(|_| -> Error {
+        format!(
+            "Can not read '{}:{}' outside of a Git repository.",
+            revision, path
+        ).into()
+    })?;
+    let workdir = match repo.workdir() {
+        Some(workdir) => fs::canonicalize(workdir)?,
+        None => return Err("Can not read files from a bare Git repository.".into()),
+    };
+
+    let path_relative_to_repo = match normalize(&current_dir.join(path)).strip_prefix(&workdir) {
+        Ok(path_relative_to_repo) => path_relative_to_repo.to_owned(),
+        Err(_) => return Err(format!("'{}' is outside of the Git repository.", path).into()),
+    };
+
+    let tree = repo
+        .revparse_single(revision)
+        .and_then(|object| object.peel_to_tree())
+        .map_err(|_| unknown_revision(revision))?;
+
+    let blob = tree
+        .get_path(&path_relative_to_repo)
+        .and_then(|entry| entry.to_object(&repo))
+        .and_then(|object| object.peel_to_blob())
+        .map_err(|_| -> Error {
+            format!("'{}' is not a file in the revision '{}'.", path, revision).into()
+        })?;
+
+    Ok(blob.content().to_vec())
+}
+
+/// Check that the given Git revision exists in the repository of the current directory. Outside
+/// of a repository, there is nothing to check against.
+pub fn check_revision(revision: &str) -> Result<()> {
+    let repo = match Repository::discover(env::current_dir()?) {
+        Ok(repo) => repo,
+        Err(_) => return Ok(()),
+    };
+
+    repo.revparse_single(revision)
+        .and_then(|object| object.peel_to_tree())
+        .map(|_| ())
+        .map_err(|_| unknown_revision(revision))
+}
+
+fn unknown_revision(revision: &str) -> Error {
+    format!("Unknown Git revision '{}'.", revision).into()
+}
+
+/// Resolve the '.' and '..' components of a path, without accessing the file system (the file
+/// does not have to exist in the working tree).
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component.as_os_str()),
+        }
+    }
+    normalized
+}
+
+#[test]
+fn test_normalize() {
+    assert_eq!(
+        PathBuf::from("/repo/src/main.rs"),
+        normalize(Path::new("/repo/doc/../src/./main.rs"))
+    );
+    assert_eq!(PathBuf::from("/repo"), normalize(Path::new("/repo/src/..")));
+}
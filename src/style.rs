@@ -6,6 +6,7 @@ use errors::*;
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
 pub enum OutputComponent {
     Auto,
+    Blame,
     Changes,
     Grid,
     Header,
@@ -17,6 +18,7 @@ pub enum OutputComponent {
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
 pub enum OutputWrap {
     Character,
+    Word,
     None,
 }
 
@@ -28,6 +30,7 @@ impl OutputComponent {
             } else {
                 OutputComponent::Plain.components(interactive_terminal)
             },
+            OutputComponent::Blame => &[OutputComponent::Blame],
             OutputComponent::Changes => &[OutputComponent::Changes],
             OutputComponent::Grid => &[OutputComponent::Grid],
             OutputComponent::Header => &[OutputComponent::Header],
@@ -49,6 +52,7 @@ impl FromStr for OutputComponent {
     fn from_str(s: &str) -> Result<Self> {
         match s {
             "auto" => Ok(OutputComponent::Auto),
+            "blame" => Ok(OutputComponent::Blame),
             "changes" => Ok(OutputComponent::Changes),
             "grid" => Ok(OutputComponent::Grid),
             "header" => Ok(OutputComponent::Header),
@@ -63,6 +67,10 @@ impl FromStr for OutputComponent {
 pub struct OutputComponents(pub HashSet<OutputComponent>);
 
 impl OutputComponents {
+    pub fn blame(&self) -> bool {
+        self.0.contains(&OutputComponent::Blame)
+    }
+
     pub fn changes(&self) -> bool {
         self.0.contains(&OutputComponent::Changes)
     }
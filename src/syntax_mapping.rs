@@ -0,0 +1,111 @@
+use std::env;
+use std::path::Path;
+
+use globset::{Glob, GlobMatcher};
+
+use errors::*;
+
+/// User-defined mappings from file name patterns to syntaxes. If several patterns match a file,
+/// the mapping which was added last wins.
+#[derive(Clone, Debug, Default)]
+pub struct SyntaxMapping {
+    mappings: Vec<(GlobMatcher, String)>,
+}
+
+impl SyntaxMapping {
+    pub fn new() -> SyntaxMapping {
+        Default::default()
+    }
+
+    /// Highlight files matching the glob pattern `glob` with the syntax `language`.
+    pub fn insert(&mut self, glob: &str, language: &str) -> Result<()> {
+        let matcher = Glob::new(glob)?.compile_matcher();
+        self.mappings.push((matcher, language.to_owned()));
+        Ok(())
+    }
+
+    /// Add a mapping of the form 'GLOB:Language'.
+    pub fn insert_from_str(&mut self, mapping: &str) -> Result<()> {
+        match mapping.rfind(':') {
+            Some(pos) if pos > 0 && pos + 1 < mapping.len() => {
+                self.insert(&mapping[..pos], &mapping[pos + 1..])
+            }
+            _ => Err(format!(
+                "Invalid syntax mapping '{}', expected 'GLOB:Language'",
+                mapping
+            ).into()),
+        }
+    }
+
+    /// Add all mappings of `other`, with a higher priority than the existing ones.
+    pub fn extend(&mut self, other: SyntaxMapping) {
+        self.mappings.extend(other.mappings);
+    }
+
+    /// The language for the file at `path`. Patterns are matched against the path as given, the
+    /// absolute path and the file name alone.
+    pub fn get_language(&self, path: &Path) -> Option<&str> {
+        let file_name = path.file_name().map(Path::new);
+        let absolute_path = if path.is_relative() {
+            env::current_dir().ok().map(|dir| dir.join(path))
+        } else {
+            None
+        };
+
+        self.mappings
+            .iter()
+            .rev()
+            .find(|&&(ref matcher, _)| {
+                matcher.is_match(path)
+                    || absolute_path.as_ref().map_or(false, |p| matcher.is_match(p))
+                    || file_name.map_or(false, |name| matcher.is_match(name))
+            }).map(|&(_, ref language)| language.as_str())
+    }
+}
+
+#[cfg(test)]
+fn mapping(mappings: &[&str]) -> SyntaxMapping {
+    let mut mapping = SyntaxMapping::new();
+    for m in mappings {
+        mapping.insert_from_str(m).unwrap();
+    }
+    mapping
+}
+
+#[test]
+fn test_get_language() {
+    let mapping = mapping(&["*.conf:INI", "Jenkinsfile:Groovy", "**/.config/git/*:Git Config"]);
+
+    assert_eq!(Some("INI"), mapping.get_language(Path::new("/etc/app.conf")));
+    assert_eq!(Some("Groovy"), mapping.get_language(Path::new("ci/Jenkinsfile")));
+    assert_eq!(
+        Some("Git Config"),
+        mapping.get_language(Path::new("/home/user/.config/git/ignore"))
+    );
+    assert_eq!(None, mapping.get_language(Path::new("src/main.rs")));
+}
+
+#[test]
+fn test_get_language_absolute_path() {
+    let mapping = mapping(&["/**/include/*.h:C++"]);
+
+    assert_eq!(Some("C++"), mapping.get_language(Path::new("include/bat.h")));
+}
+
+#[test]
+fn test_get_language_last_mapping_wins() {
+    let mut mapping = mapping(&["*.h:C", "*.inc:C"]);
+    mapping.extend(self::mapping(&["*.h:C++"]));
+
+    assert_eq!(Some("C++"), mapping.get_language(Path::new("include/bat.h")));
+    assert_eq!(Some("C"), mapping.get_language(Path::new("include/bat.inc")));
+}
+
+#[test]
+fn test_insert_from_str_invalid() {
+    let mut mapping = SyntaxMapping::new();
+    assert!(mapping.insert_from_str("*.conf").is_err());
+    assert!(mapping.insert_from_str(":INI").is_err());
+    assert!(mapping.insert_from_str("*.conf:").is_err());
+    assert!(mapping.insert_from_str("a{b:INI").is_err());
+}
@@ -35,13 +35,14 @@ pub fn as_terminal_escaped(
     text: &str,
     true_color: bool,
     colored: bool,
+    background_color: Option<highlighting::Color>,
 ) -> String {
     let style = if !colored {
         Style::default()
     } else {
         let color = to_ansi_color(style.foreground, true_color);
 
-        if style.font_style.contains(FontStyle::BOLD) {
+        let style = if style.font_style.contains(FontStyle::BOLD) {
             color.bold()
         } else if style.font_style.contains(FontStyle::UNDERLINE) {
             color.underline()
@@ -49,12 +50,38 @@ pub fn as_terminal_escaped(
             color.italic()
         } else {
             color.normal()
+        };
+
+        match background_color {
+            Some(background_color) => style.on(to_ansi_color(background_color, true_color)),
+            None => style,
         }
     };
 
     style.paint(text).to_string()
 }
 
+#[test]
+fn test_as_terminal_escaped_background() {
+    let style = highlighting::Style {
+        foreground: highlighting::Color::WHITE,
+        background: highlighting::Color::BLACK,
+        font_style: FontStyle::empty(),
+    };
+    let background = Some(highlighting::Color {
+        r: 0x40,
+        g: 0x40,
+        b: 0x40,
+        a: 0xff,
+    });
+
+    assert_eq!(
+        "\x1b[48;2;64;64;64;38;2;255;255;255mtext\x1b[0m",
+        as_terminal_escaped(style, "text", true, true, background)
+    );
+    assert_eq!("text", as_terminal_escaped(style, "text", true, false, background));
+}
+
 #[test]
 fn test_rgb2ansi_black_white() {
     assert_eq!(16, rgb2ansi(0x00, 0x00, 0x00));
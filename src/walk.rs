@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+
+use errors::*;
+
+/// Decides which of the files found in a directory are printed, based on glob patterns that are
+/// matched against the path relative to that directory.
+#[derive(Clone, Debug)]
+pub struct FileFilter {
+    include: GlobSet,
+    exclude: GlobSet,
+}
+
+impl FileFilter {
+    /// Files need to match at least one of the `include` patterns (if any are given) and none of
+    /// the `exclude` patterns.
+    pub fn new(include: &[&str], exclude: &[&str]) -> Result<FileFilter> {
+        Ok(FileFilter {
+            include: build_glob_set(include)?,
+            exclude: build_glob_set(exclude)?,
+        })
+    }
+
+    pub fn all() -> FileFilter {
+        FileFilter {
+            include: GlobSet::empty(),
+            exclude: GlobSet::empty(),
+        }
+    }
+
+    pub fn is_match(&self, path: &Path) -> bool {
+        (self.include.is_empty() || self.include.is_match(path)) && !self.exclude.is_match(path)
+    }
+}
+
+fn build_glob_set(patterns: &[&str]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// All files below `dir` which pass the `filter`, sorted by path. Hidden files and files ignored
+/// by Git are skipped.
+pub fn files<'a>(
+    dir: &'a str,
+    filter: &'a FileFilter,
+) -> impl Iterator<Item = Result<String>> + 'a {
+    WalkBuilder::new(dir)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+        .filter_map(move |entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => return Some(Err(error.into())),
+            };
+
+            let path = entry.path();
+            let relative_path = path.strip_prefix(dir).unwrap_or(path);
+            if !path.is_file() || !filter.is_match(relative_path) {
+                return None;
+            }
+
+            Some(
+                path.to_str()
+                    .map(String::from)
+                    .ok_or_else(|| format!("'{}' is not valid UTF-8", path.display()).into()),
+            )
+        })
+}
+
+#[test]
+fn test_file_filter() {
+    let filter = FileFilter::new(&["*.rs", "Cargo.toml"], &["tests/**"]).unwrap();
+    assert!(filter.is_match(Path::new("main.rs")));
+    assert!(filter.is_match(Path::new("src/main.rs")));
+    assert!(filter.is_match(Path::new("Cargo.toml")));
+    assert!(!filter.is_match(Path::new("README.md")));
+    assert!(!filter.is_match(Path::new("tests/tester.rs")));
+
+    let filter = FileFilter::new(&[], &["*.bin"]).unwrap();
+    assert!(filter.is_match(Path::new("README.md")));
+    assert!(!filter.is_match(Path::new("assets/themes.bin")));
+
+    assert!(FileFilter::all().is_match(Path::new("anything")));
+}
+
+#[test]
+fn test_invalid_glob() {
+    assert!(FileFilter::new(&["[a-"], &[]).is_err());
+}
@@ -0,0 +1,176 @@
+use unicode_width::UnicodeWidthStr;
+
+use style::OutputWrap;
+
+/// What to do with a grapheme cluster when printing a wrapped line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WrapAction {
+    /// Print it on the current line
+    Print,
+    /// Start a continuation line and print it there
+    Wrap,
+    /// Leave it out, because it is whitespace at a line break
+    Skip,
+}
+
+fn is_whitespace(grapheme: &str) -> bool {
+    grapheme.chars().all(char::is_whitespace)
+}
+
+/// Whether a line may be broken after the given (non-whitespace) grapheme
+fn is_break_after(grapheme: &str) -> bool {
+    grapheme
+        .chars()
+        .all(|c| c.is_ascii_punctuation() && c != '_')
+}
+
+/// Split the grapheme indices `0..graphemes.len()` into words. Every word consists of the
+/// non-whitespace part (ending after punctuation) and the whitespace that follows it.
+fn words(graphemes: &[&str]) -> Vec<(usize, usize, usize)> {
+    let mut words = vec![];
+    let mut start = 0;
+
+    while start < graphemes.len() {
+        let mut whitespace_start = start;
+        while whitespace_start < graphemes.len() && !is_whitespace(graphemes[whitespace_start]) {
+            whitespace_start += 1;
+            if is_break_after(graphemes[whitespace_start - 1]) {
+                break;
+            }
+        }
+
+        let mut end = whitespace_start;
+        while end < graphemes.len() && is_whitespace(graphemes[end]) {
+            end += 1;
+        }
+
+        words.push((start, whitespace_start, end));
+        start = end;
+    }
+
+    words
+}
+
+/// Determine how to print the grapheme clusters of a line such that no part of it is wider than
+/// `width` columns.
+pub fn wrap(graphemes: &[&str], width: usize, mode: OutputWrap) -> Vec<WrapAction> {
+    let mut actions = vec![WrapAction::Print; graphemes.len()];
+    let mut cursor = 0;
+
+    // Start a continuation line before the `i`-th grapheme if it does not fit anymore, but never
+    // leave the current line empty.
+    let wrap_before = |i: usize, cursor: &mut usize, actions: &mut Vec<WrapAction>| {
+        let grapheme_width = graphemes[i].width();
+        if *cursor + grapheme_width > width && *cursor > 0 {
+            actions[i] = WrapAction::Wrap;
+            *cursor = 0;
+        }
+        *cursor += grapheme_width;
+    };
+
+    if mode != OutputWrap::Word {
+        for i in 0..graphemes.len() {
+            wrap_before(i, &mut cursor, &mut actions);
+        }
+        return actions;
+    }
+
+    for (start, whitespace_start, end) in words(graphemes) {
+        let word_width: usize = graphemes[start..whitespace_start]
+            .iter()
+            .map(|g| g.width())
+            .sum();
+
+        if word_width > width {
+            // Overlong words are wrapped by character.
+            for i in start..whitespace_start {
+                wrap_before(i, &mut cursor, &mut actions);
+            }
+        } else if word_width > 0 {
+            if cursor + word_width > width {
+                actions[start] = WrapAction::Wrap;
+                cursor = 0;
+            }
+            cursor += word_width;
+        }
+
+        // Whitespace that does not fit anymore is left out, the next word starts a new line.
+        for i in whitespace_start..end {
+            let grapheme_width = graphemes[i].width();
+            if cursor + grapheme_width > width {
+                actions[i] = WrapAction::Skip;
+                cursor = width;
+            } else {
+                cursor += grapheme_width;
+            }
+        }
+    }
+
+    actions
+}
+
+#[cfg(test)]
+fn wrapped(text: &str, width: usize, mode: OutputWrap) -> String {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let graphemes = text.graphemes(true).collect::<Vec<_>>();
+    let actions = wrap(&graphemes, width, mode);
+
+    let mut output = String::new();
+    for (grapheme, action) in graphemes.iter().zip(actions) {
+        match action {
+            WrapAction::Print => output.push_str(grapheme),
+            WrapAction::Wrap => {
+                output.push('\n');
+                output.push_str(grapheme);
+            }
+            WrapAction::Skip => {}
+        }
+    }
+    output
+}
+
+#[test]
+fn test_wrap_character() {
+    assert_eq!(
+        "the quic\nk brown \nfox",
+        wrapped("the quick brown fox", 8, OutputWrap::Character)
+    );
+    assert_eq!("漢字漢\n字", wrapped("漢字漢字", 7, OutputWrap::Character));
+}
+
+#[test]
+fn test_wrap_word() {
+    assert_eq!(
+        "the \nquick \nbrown \nfox",
+        wrapped("the quick brown fox", 8, OutputWrap::Word)
+    );
+    assert_eq!(
+        "the quick\nbrown fox",
+        wrapped("the quick brown fox", 9, OutputWrap::Word)
+    );
+}
+
+#[test]
+fn test_wrap_word_punctuation() {
+    assert_eq!(
+        "self.\nsome_field.\ncall()",
+        wrapped("self.some_field.call()", 12, OutputWrap::Word)
+    );
+}
+
+#[test]
+fn test_wrap_word_overlong() {
+    assert_eq!(
+        "a verylon\ngword b",
+        wrapped("a verylongword b", 9, OutputWrap::Word)
+    );
+}
+
+#[test]
+fn test_wrap_word_indentation() {
+    assert_eq!(
+        "    let \nx = 1;",
+        wrapped("    let x = 1;", 8, OutputWrap::Word)
+    );
+}
@@ -0,0 +1 @@
+fn main() { let greeting = "hello wonderful world"; }
@@ -1,8 +1,8 @@
 use std::env;
 use std::fs::{self, File};
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output, Stdio};
 
 extern crate tempdir;
 use self::tempdir::TempDir;
@@ -39,30 +39,146 @@ impl BatTester {
     }
 
     pub fn test_snapshot(&self, style: &str) {
-        let output = Command::new(&self.exe)
+        let actual = self.run(style, &[]);
+        assert_eq!(snapshot(style), actual);
+    }
+
+    /// Print `sample.rs` with the given style and additional arguments.
+    pub fn run(&self, style: &str, args: &[&str]) -> String {
+        self.run_input("sample.rs", style, args)
+    }
+
+    /// Print the given input with the given style and additional arguments.
+    pub fn run_input(&self, input: &str, style: &str, args: &[&str]) -> String {
+        let output = self.output(input, style, args);
+
+        // have to do the replace because the filename in the header changes based on the current working directory
+        String::from_utf8_lossy(&output.stdout)
+            .as_ref()
+            .replace("tests/snapshots/", "")
+    }
+
+    /// Print the given content from STDIN with the given style and additional arguments.
+    pub fn run_stdin(&self, stdin: &[u8], style: &str, args: &[&str]) -> String {
+        let mut child = Command::new(&self.exe)
+            .current_dir(self.temp_dir.path())
+            .env("BAT_CONFIG_PATH", self.config_file())
+            .args(&["--decorations=always", &format!("--style={}", style)])
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("bat failed");
+        child
+            .stdin
+            .take()
+            .expect("stdin")
+            .write_all(stdin)
+            .expect("write to stdin");
+
+        let output = child.wait_with_output().expect("bat failed");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    /// Run bat on the given input with the given style and additional arguments, and return its
+    /// exit status along with everything it printed.
+    pub fn output(&self, input: &str, style: &str, args: &[&str]) -> Output {
+        Command::new(&self.exe)
             .current_dir(self.temp_dir.path())
+            .env("BAT_CONFIG_PATH", self.config_file())
             .args(&[
-                "sample.rs",
+                input,
                 "--decorations=always",
                 &format!("--style={}", style),
-            ]).output()
-            .expect("bat failed");
+            ]).args(args)
+            .output()
+            .expect("bat failed")
+    }
 
-        // have to do the replace because the filename in the header changes based on the current working directory
-        let actual = String::from_utf8_lossy(&output.stdout)
-            .as_ref()
-            .replace("tests/snapshots/", "");
+    /// Print the given input with additional arguments, like `cat` does when the output is piped
+    /// to another program.
+    pub fn run_piped(&self, input: &str, args: &[&str]) -> Vec<u8> {
+        Command::new(&self.exe)
+            .current_dir(self.temp_dir.path())
+            .env("BAT_CONFIG_PATH", self.config_file())
+            .arg(input)
+            .args(args)
+            .output()
+            .expect("bat failed")
+            .stdout
+    }
+
+    /// The configuration file used by the tests, which does not exist until it is written.
+    fn config_file(&self) -> PathBuf {
+        self.temp_dir.path().join("config")
+    }
+
+    /// Write the configuration file with the given default arguments.
+    pub fn write_config_file(&self, contents: &str) {
+        fs::write(self.config_file(), contents).expect("config file");
+    }
+
+    /// Replace the contents of `sample.rs` in the working tree.
+    pub fn write_sample(&self, contents: &[u8]) {
+        self.write_file("sample.rs", contents);
+    }
+
+    /// Write a file in the working tree.
+    pub fn write_file(&self, name: &str, contents: &[u8]) {
+        fs::write(self.temp_dir.path().join(name), contents).expect("file in working tree");
+    }
+
+    /// Add the modifications of `sample.rs` to the index.
+    pub fn stage_sample(&self) {
+        let repo = Repository::open(self.temp_dir.path()).expect("repository");
+        let mut index = repo.index().expect("index");
+        index.add_path(Path::new("sample.rs")).expect("add to index");
+        index.write().expect("write index");
+    }
 
-        let mut expected = String::new();
-        let mut file = File::open(format!("tests/snapshots/output/{}.snapshot.txt", style))
-            .expect("snapshot file missing");
-        file.read_to_string(&mut expected)
-            .expect("could not read snapshot file");
+    /// Commit the modifications of `sample.rs`.
+    pub fn commit_sample(&self) {
+        self.commit_sample_as("bat test runner");
+    }
 
-        assert_eq!(expected, actual);
+    /// Commit the modifications of `sample.rs` with the given author name, and return the id of
+    /// the commit.
+    pub fn commit_sample_as(&self, author: &str) -> String {
+        self.stage_sample();
+
+        let repo = Repository::open(self.temp_dir.path()).expect("repository");
+        let oid = repo
+            .index()
+            .and_then(|mut index| index.write_tree())
+            .expect("tree");
+        let tree = repo.find_tree(oid).expect("tree");
+        let parent = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .expect("parent commit");
+        let signature = Signature::now(author, "bat@test.runner").expect("signature");
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "modify sample",
+            &tree,
+            &[&parent],
+        ).expect("commit")
+        .to_string()
     }
 }
 
+/// The expected output for the given snapshot name.
+pub fn snapshot(name: &str) -> String {
+    let mut expected = String::new();
+    let mut file = File::open(format!("tests/snapshots/output/{}.snapshot.txt", name))
+        .expect("snapshot file missing");
+    file.read_to_string(&mut expected)
+        .expect("could not read snapshot file");
+    expected
+}
+
 fn create_sample_directory() -> Result<TempDir, git2::Error> {
     // Create temp directory and initialize repository
     let temp_dir = TempDir::new("bat-tests").expect("Temp directory");
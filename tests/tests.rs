@@ -1,6 +1,16 @@
+extern crate bat;
+extern crate regex;
+
 mod tester;
 
-use tester::BatTester;
+use std::fs::{self, File};
+use std::io::Read;
+
+use bat::line_range::{LineRange, LineRanges};
+use bat::style::OutputWrap;
+use bat::PrettyPrinter;
+use regex::Regex;
+use tester::{snapshot, BatTester};
 
 static STYLES: &'static [&'static str] = &[
     "changes",
@@ -31,3 +41,461 @@ fn test_snapshots() {
         bat_tester.test_snapshot(&*style);
     }
 }
+
+/// Replace the markers for (unstaged) modifications in the first column of a snapshot.
+fn replace_markers(snapshot: &str, added: &str, modified: &str) -> String {
+    snapshot
+        .lines()
+        .map(|line| match line.chars().next() {
+            Some('+') => format!("{}{}\n", added, &line[1..]),
+            Some('~') => format!("{}{}\n", modified, &line[1..]),
+            _ => format!("{}\n", line),
+        }).collect()
+}
+
+#[test]
+fn test_staged_changes() {
+    let bat_tester = BatTester::new();
+    bat_tester.stage_sample();
+
+    assert_eq!(
+        replace_markers(&snapshot("changes"), "✚", "≈"),
+        bat_tester.run("changes", &[])
+    );
+}
+
+#[test]
+fn test_diff_base() {
+    let bat_tester = BatTester::new();
+    bat_tester.commit_sample();
+
+    // The working tree is clean, but the file differs from the previous commit.
+    let unmodified = bat_tester.run("changes", &[]);
+    assert!(unmodified.lines().all(|line| line.starts_with(' ')));
+    assert_eq!(
+        replace_markers(&snapshot("changes"), "✚", "≈"),
+        bat_tester.run("changes", &["--diff-base", "HEAD~1"])
+    );
+
+    // An unknown base is an error, instead of showing no modifications.
+    let output = bat_tester.output("sample.rs", "changes", &["--diff-base", "HEAD~5"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown Git revision 'HEAD~5'"));
+}
+
+#[test]
+fn test_config_file() {
+    let bat_tester = BatTester::new();
+    bat_tester.write_config_file("--line-range 1:2\n--highlight-line 1\n");
+
+    assert_eq!(
+        "struct Rectangle {\n    width: u32,\n",
+        bat_tester.run("plain", &[])
+    );
+
+    // Options which can be given multiple times replace the values from the configuration file.
+    assert_eq!("}\n", bat_tester.run("plain", &["--line-range", "4"]));
+}
+
+#[test]
+fn test_invalid_config_file() {
+    let bat_tester = BatTester::new();
+    bat_tester.write_config_file("--theme=\"TwoDark\n");
+
+    assert_eq!("}\n", bat_tester.run("plain", &["--line-range", "4"]));
+}
+
+#[test]
+fn test_invalid_config_file_arguments() {
+    let bat_tester = BatTester::new();
+    bat_tester.write_config_file("--tabs=two\n--line-range 1:2\n");
+
+    let output = bat_tester.output("sample.rs", "plain", &["--line-range", "4"]);
+    assert_eq!("}\n", String::from_utf8_lossy(&output.stdout));
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("Ignoring the arguments from the configuration file")
+    );
+}
+
+#[test]
+fn test_invalid_project_config() {
+    let bat_tester = BatTester::new();
+    bat_tester.write_file(".bat.toml", b"tabs = \"two\"\n");
+
+    assert_eq!("}\n", bat_tester.run("plain", &["--line-range", "4"]));
+}
+
+#[test]
+fn test_compressed_input_piped() {
+    let bat_tester = BatTester::new();
+    bat_tester.write_file(
+        "compressed.txt.gz",
+        &fs::read("tests/examples/compressed.txt.gz").expect("compressed example"),
+    );
+
+    assert_eq!(
+        "line 1\nline 2\n",
+        String::from_utf8_lossy(&bat_tester.run_piped("compressed.txt.gz", &[]))
+    );
+}
+
+#[test]
+fn test_encoded_input_piped() {
+    let bat_tester = BatTester::new();
+    bat_tester.write_file(
+        "utf16.txt",
+        &fs::read("tests/examples/modeline-utf16.txt").expect("UTF-16 example"),
+    );
+    bat_tester.write_file("latin1.txt", b"caf\xe9\n");
+
+    let utf16 = bat_tester.run_piped("utf16.txt", &[]);
+    assert!(String::from_utf8(utf16).unwrap().starts_with("echo \"line 1\"\n"));
+    assert_eq!(
+        "café\n",
+        String::from_utf8(bat_tester.run_piped("latin1.txt", &["--encoding", "latin1"])).unwrap()
+    );
+}
+
+#[test]
+fn test_diff_only() {
+    let bat_tester = BatTester::new();
+
+    assert_eq!(
+        "   6 fn main() {\n\
+         --\n  \
+         10         \"The perimeter of the rectangle is {} pixels.\",\n  \
+         11         perimeter(&rect1)\n",
+        bat_tester.run("numbers", &["--diff", "--diff-context", "0", "--line-range", ":12"])
+    );
+
+    // Files without modifications are skipped.
+    bat_tester.commit_sample();
+    assert_eq!("", bat_tester.run("numbers", &["--diff"]));
+
+    // So is input which is not part of a repository.
+    assert_eq!("", bat_tester.run_stdin(b"line 1\n", "numbers", &["--diff"]));
+}
+
+#[test]
+fn test_inline_diff() {
+    let bat_tester = BatTester::new();
+
+    assert_eq!(
+        "   6 _ fn main() {\n\
+         \x20          // width and height of a rectangle can be different\n\
+         --\n\
+         \x20              \"The area of the rectangle is {} square pixels.\",\n\
+         \x20              area(&rect1)\n  \
+         10 ~         \"The perimeter of the rectangle is {} pixels.\",\n  \
+         11 ~         perimeter(&rect1)\n",
+        bat_tester.run(
+            "numbers,changes",
+            &["--inline-diff", "--diff", "--diff-context", "0", "--line-range", ":12"]
+        )
+    );
+}
+
+#[test]
+fn test_inline_diff_at_end_of_file() {
+    let bat_tester = BatTester::new();
+    bat_tester.commit_sample();
+
+    // Remove everything after line 18.
+    let sample = fs::read("tests/snapshots/sample.modified.rs").expect("sample file");
+    let lines = sample.split(|&b| b == b'\n').take(18).collect::<Vec<_>>();
+    bat_tester.write_sample(&[&lines.join(&b'\n')[..], b"\n"].concat());
+
+    // The removed lines are printed after the last line.
+    assert_eq!(
+        "  17       rectangle.width * rectangle.height\n  \
+         18 _ }\n\
+         \x20      \n\
+         \x20      fn perimeter(rectangle: &Rectangle) -> u32 {\n\
+         \x20          (rectangle.width + rectangle.height) * 2\n\
+         \x20      }\n",
+        bat_tester.run("numbers,changes", &["--inline-diff", "--line-range", "17:"])
+    );
+}
+
+#[test]
+fn test_inline_diff_encoding() {
+    let bat_tester = BatTester::new();
+    bat_tester.commit_sample();
+
+    // Remove line 13, which is not valid UTF-8.
+    let sample = fs::read("tests/snapshots/sample.modified.rs").expect("sample file");
+    let mut lines = sample.split(|&b| b == b'\n').collect::<Vec<_>>();
+    lines.remove(12);
+    bat_tester.write_sample(&lines.join(&b'\n'));
+
+    // The removed line is decoded like the rest of the file.
+    assert_eq!(
+        "  12     );\n\
+         \x20        println!(r#\"This line contains invalid utf8:  \"øˆ€€€\"#;\n  \
+         13 }\n",
+        bat_tester.run(
+            "numbers",
+            &["--inline-diff", "--encoding", "latin1", "--line-range", "12:13"]
+        )
+    );
+}
+
+#[test]
+fn test_blame() {
+    let bat_tester = BatTester::new();
+    let output = bat_tester.run("blame", &["--line-range", "9:11"]);
+
+    let committed = Regex::new(r"^[0-9a-f]{7} BTR [ \w]{9}     println!\($").unwrap();
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(3, lines.len());
+    assert!(committed.is_match(lines[0]));
+    assert!(lines[1].starts_with("Not committed yet             \"The perimeter"));
+    assert!(lines[2].starts_with("Not committed yet             perimeter(&rect1)"));
+}
+
+#[test]
+fn test_blame_ignored_revision() {
+    let bat_tester = BatTester::new();
+    bat_tester.commit_sample_as("Another Author");
+
+    // Reformat the file: add two lines at the start and reindent line 10 (which is now line 12).
+    let sample = fs::read("tests/snapshots/sample.modified.rs").expect("sample file");
+    let mut lines = sample.split(|&b| b == b'\n').collect::<Vec<_>>();
+    let reindented = [b"\t\t", &lines[9][8..]].concat();
+    lines[9] = &reindented;
+    lines.insert(0, b"// Reformatted");
+    lines.insert(1, b"");
+    bat_tester.write_sample(&lines.join(&b'\n'));
+    let reformat = bat_tester.commit_sample_as("Format Bot");
+    bat_tester.write_file(".git-blame-ignore-revs", reformat.as_bytes());
+
+    // The reindented line is attributed to the commit before the reformatting, lines which were
+    // added by it still to the reformatting itself.
+    let blamed_on = |line: &str| {
+        let output = bat_tester.run("blame", &["--line-range", line]);
+        output.split_whitespace().nth(1).unwrap_or("").to_owned()
+    };
+    assert_eq!("AA", blamed_on("12"));
+    assert_eq!("FB", blamed_on("1"));
+}
+
+#[test]
+fn test_revision() {
+    let bat_tester = BatTester::new();
+
+    let mut original = String::new();
+    File::open("tests/snapshots/sample.rs")
+        .and_then(|mut file| file.read_to_string(&mut original))
+        .expect("sample file");
+
+    assert_eq!(original, bat_tester.run_input("HEAD:sample.rs", "plain", &[]));
+    assert_eq!(original, bat_tester.run("plain", &["--rev", "HEAD"]));
+
+    let output = bat_tester.run_input("HEAD:sample.rs", "header", &[]);
+    assert_eq!(Some("File: HEAD:sample.rs"), output.lines().next());
+}
+
+#[test]
+fn test_pretty_printer_plain() {
+    let mut output = Vec::new();
+    let success = PrettyPrinter::new()
+        .input_file("tests/snapshots/sample.modified.rs")
+        .colored_output(false)
+        .print_with_writer(&mut output)
+        .expect("pretty printer failed");
+    assert!(success);
+
+    let mut expected = String::new();
+    File::open("tests/snapshots/output/plain.snapshot.txt")
+        .expect("snapshot file missing")
+        .read_to_string(&mut expected)
+        .expect("could not read snapshot file");
+
+    assert_eq!(expected, String::from_utf8_lossy(&output));
+}
+
+#[test]
+fn test_pretty_printer_wrap_display_width() {
+    let mut output = Vec::new();
+    PrettyPrinter::new()
+        .input_file("tests/examples/wide-characters.txt")
+        .colored_output(false)
+        .term_width(5)
+        .wrapping_mode(OutputWrap::Character)
+        .print_with_writer(&mut output)
+        .expect("pretty printer failed");
+
+    // Double-width characters take two columns, combining marks stay with their base character.
+    assert_eq!(
+        "漢字\n漢字\n漢字\ne\u{301}e\u{301}e\u{301}e\u{301}e\u{301}\ne\u{301}\n",
+        String::from_utf8_lossy(&output)
+    );
+}
+
+#[test]
+fn test_pretty_printer_wrap_word() {
+    let mut output = Vec::new();
+    PrettyPrinter::new()
+        .input_file("tests/examples/word-wrap.rs")
+        .colored_output(false)
+        .line_numbers(true)
+        .term_width(25)
+        .wrapping_mode(OutputWrap::Word)
+        .print_with_writer(&mut output)
+        .expect("pretty printer failed");
+
+    // Continuation lines are indented by the (empty) line number panel.
+    assert_eq!(
+        "   1 fn main() { let \n     greeting = \"hello \n     wonderful world\"; }\n",
+        String::from_utf8_lossy(&output)
+    );
+}
+
+#[test]
+fn test_pretty_printer_highlighted_lines_are_padded() {
+    let mut output = Vec::new();
+    PrettyPrinter::new()
+        .input_file("tests/examples/wide-characters.txt")
+        .true_color(false)
+        .term_width(5)
+        .wrapping_mode(OutputWrap::Character)
+        .highlighted_lines(LineRanges::from(vec![LineRange::from("1").unwrap()]))
+        .print_with_writer(&mut output)
+        .expect("pretty printer failed");
+
+    // Only the text and the padding are left without the escape sequences.
+    assert_eq!(
+        "漢字 \n漢字 \n漢字 \ne\u{301}e\u{301}e\u{301}e\u{301}e\u{301}\ne\u{301}\n",
+        strip_escape_sequences(&output)
+    );
+}
+
+#[test]
+fn test_pretty_printer_highlighted_lines_wider_than_terminal() {
+    let mut output = Vec::new();
+    PrettyPrinter::new()
+        .input_file("tests/examples/wide-characters.txt")
+        .true_color(false)
+        .term_width(1)
+        .wrapping_mode(OutputWrap::Character)
+        .highlighted_lines(LineRanges::from(vec![LineRange::from("1").unwrap()]))
+        .print_with_writer(&mut output)
+        .expect("pretty printer failed");
+
+    // Every double-width character overflows the single column, so there is nothing to pad.
+    assert_eq!(
+        "漢\n字\n漢\n字\n漢\n字\n\
+         e\u{301}\ne\u{301}\ne\u{301}\ne\u{301}\ne\u{301}\ne\u{301}\n",
+        strip_escape_sequences(&output)
+    );
+}
+
+/// Remove all escape sequences from the output
+fn strip_escape_sequences(output: &[u8]) -> String {
+    let mut plain = String::new();
+    let mut in_escape_sequence = false;
+    for c in String::from_utf8_lossy(output).chars() {
+        match c {
+            '\x1b' => in_escape_sequence = true,
+            'm' if in_escape_sequence => in_escape_sequence = false,
+            _ if !in_escape_sequence => plain.push(c),
+            _ => {}
+        }
+    }
+    plain
+}
+
+#[test]
+fn test_pretty_printer_only_matching_lines() {
+    let mut output = Vec::new();
+    PrettyPrinter::new()
+        .input_file("tests/examples/numbered-lines.txt")
+        .colored_output(false)
+        .line_numbers(true)
+        .pattern(Regex::new("line 1[05]$").unwrap())
+        .only_matching_lines(1)
+        .print_with_writer(&mut output)
+        .expect("pretty printer failed");
+
+    assert_eq!(
+        "   9 line 9\n  10 line 10\n  11 line 11\n--\n  14 line 14\n  15 line 15\n  16 line 16\n",
+        String::from_utf8_lossy(&output)
+    );
+}
+
+#[test]
+fn test_pretty_printer_separator_with_grid() {
+    let mut output = Vec::new();
+    PrettyPrinter::new()
+        .input_file("tests/examples/numbered-lines.txt")
+        .colored_output(false)
+        .line_numbers(true)
+        .grid(true)
+        .term_width(20)
+        .line_ranges(LineRanges::from(vec![
+            LineRange::from("1:2").unwrap(),
+            LineRange::from("5").unwrap(),
+        ])).print_with_writer(&mut output)
+        .expect("pretty printer failed");
+
+    assert_eq!(
+        "   1 │ line 1\n   2 │ line 2\n ... │ ──── 8< ─────\n   5 │ line 5\n─────┴──────────────\n",
+        String::from_utf8_lossy(&output)
+    );
+}
+
+#[test]
+fn test_pretty_printer_file_name() {
+    let mut output = Vec::new();
+    PrettyPrinter::new()
+        .input_file("tests/examples/numbered-lines.txt")
+        .file_name("renamed.txt")
+        .colored_output(false)
+        .header(true)
+        .line_ranges(LineRanges::from(vec![LineRange::from("1").unwrap()]))
+        .print_with_writer(&mut output)
+        .expect("pretty printer failed");
+
+    assert_eq!("File: renamed.txt\nline 1\n", String::from_utf8_lossy(&output));
+}
+
+#[test]
+fn test_pretty_printer_file_name_before_input() {
+    let mut output = Vec::new();
+    PrettyPrinter::new()
+        .file_name("renamed.txt")
+        .input_files(vec![
+            "tests/examples/numbered-lines.txt",
+            "tests/examples/numbered-lines.txt",
+        ]).colored_output(false)
+        .header(true)
+        .line_ranges(LineRanges::from(vec![LineRange::from("1").unwrap()]))
+        .print_with_writer(&mut output)
+        .expect("pretty printer failed");
+
+    assert_eq!(
+        "File: renamed.txt\nline 1\nFile: tests/examples/numbered-lines.txt\nline 1\n",
+        String::from_utf8_lossy(&output)
+    );
+}
+
+#[test]
+fn test_pretty_printer_modeline_in_last_lines_of_utf16_file() {
+    let print = |language: Option<&str>| {
+        let mut output = Vec::new();
+        let mut printer = PrettyPrinter::new();
+        printer.input_file("tests/examples/modeline-utf16.txt");
+        if let Some(language) = language {
+            printer.language(language);
+        }
+        printer
+            .print_with_writer(&mut output)
+            .expect("pretty printer failed");
+        output
+    };
+
+    // The modeline is only found if the end of the file is decoded like the rest of it.
+    assert_eq!(print(Some("sh")), print(None));
+    assert_ne!(print(Some("txt")), print(None));
+}